@@ -0,0 +1,226 @@
+//! FlexRAM partitioning
+//!
+//! The RT1062 has 512K of FlexRAM, in sixteen 32K banks, each of which
+//! can be used as ITCM, DTCM or OCRAM. The split is chosen at build
+//! time through the `TEENSY40_ITCM_KB` and `TEENSY40_DTCM_KB`
+//! environment variables, with whatever is left over going to OCRAM.
+//! The defaults match the chip's fused configuration of 128K ITCM,
+//! 128K DTCM and 256K OCRAM.
+//!
+//! From the split we generate the linker script's `MEMORY` block, and
+//! the register values `startup` programs into `IOMUXC_GPR14`, `16`
+//! and `17`. We also decide whether `.text` stays in flash, or is
+//! moved into ITCM along with `.fastrun` by the `itcm-text` feature.
+
+use std::{env, fmt::Write};
+
+pub const ITCM_VAR: &str = "TEENSY40_ITCM_KB";
+pub const DTCM_VAR: &str = "TEENSY40_DTCM_KB";
+
+/// The size of a single FlexRAM bank, in KiB
+const BANK_KB: u32 = 32;
+/// The number of FlexRAM banks
+const BANKS: u32 = 16;
+
+/// The FlexRAM split, in KiB
+#[derive(Debug, PartialEq)]
+pub struct Split {
+    pub itcm: u32,
+    pub dtcm: u32,
+    pub ocram: u32,
+}
+
+fn size_from_env(var: &str, default: u32) -> u32 {
+    match env::var(var) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be a size in KiB, not {:?}", var, value)),
+        Err(env::VarError::NotPresent) => default,
+        Err(err) => panic!("Could not read {}: {}", var, err),
+    }
+}
+
+/// Read the split from the environment, and check it can be
+/// programmed into the FlexRAM
+pub fn split() -> Split {
+    let itcm = size_from_env(ITCM_VAR, 128);
+    let dtcm = size_from_env(DTCM_VAR, 128);
+    check_split(itcm, dtcm).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Check that `itcm` and `dtcm` KiB can be programmed into the
+/// FlexRAM, and give the OCRAM the rest
+pub fn check_split(itcm: u32, dtcm: u32) -> Result<Split, String> {
+    for &(var, size) in &[(ITCM_VAR, itcm), (DTCM_VAR, dtcm)] {
+        if size != 0 && (size < BANK_KB || !size.is_power_of_two()) {
+            return Err(format!(
+                "{} is {}K, but the TCMs can only be 0K or a power of two from {}K up",
+                var, size, BANK_KB
+            ));
+        }
+    }
+    if dtcm == 0 {
+        return Err(format!(
+            "{} is 0K, but the DTCM holds .data, .bss and the stack",
+            DTCM_VAR
+        ));
+    }
+    if itcm + dtcm > BANKS * BANK_KB {
+        return Err(format!(
+            "{}K of ITCM and {}K of DTCM is more than the {}K of FlexRAM",
+            itcm,
+            dtcm,
+            BANKS * BANK_KB
+        ));
+    }
+
+    Ok(Split {
+        itcm,
+        dtcm,
+        ocram: BANKS * BANK_KB - itcm - dtcm,
+    })
+}
+
+/// The `MEMORY` block for the linker script
+pub fn memory(split: &Split) -> String {
+    let mut out = String::new();
+    writeln!(out, "MEMORY").unwrap();
+    writeln!(out, "{{").unwrap();
+    writeln!(
+        out,
+        "\tITCM (rwx):  ORIGIN = 0x00000000, LENGTH = {}K",
+        split.itcm
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "\tDTCM (rwx):  ORIGIN = 0x20000000, LENGTH = {}K",
+        split.dtcm
+    )
+    .unwrap();
+    writeln!(out, "\tRAM (rwx):   ORIGIN = 0x20200000, LENGTH = 512K").unwrap();
+    writeln!(
+        out,
+        "\tOCRAM (rwx): ORIGIN = 0x20280000, LENGTH = {}K",
+        split.ocram
+    )
+    .unwrap();
+    writeln!(out, "\tFLASH (rwx): ORIGIN = 0x60000000, LENGTH = 1984K").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// The `CM7_CFGxTCMSZ` encoding of a TCM size
+fn tcm_size(kb: u32) -> u32 {
+    if kb == 0 {
+        0
+    } else {
+        kb.trailing_zeros() + 1
+    }
+}
+
+/// The register values which `startup` programs
+pub fn config(split: &Split) -> String {
+    // Banks are handed out ITCM first, then DTCM, then OCRAM, two bits
+    // per bank in FLEXRAM_BANK_CFG
+    let itcm_banks = split.itcm / BANK_KB;
+    let dtcm_banks = split.dtcm / BANK_KB;
+    let bank_cfg = (0..BANKS).fold(0, |cfg, bank| {
+        let kind = if bank < itcm_banks {
+            3
+        } else if bank < itcm_banks + dtcm_banks {
+            2
+        } else {
+            1
+        };
+        cfg | kind << (2 * bank)
+    });
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// {}K ITCM, {}K DTCM, {}K OCRAM",
+        split.itcm, split.dtcm, split.ocram
+    )
+    .unwrap();
+    writeln!(out, "const FLEXRAM_BANK_CFG: u32 = {:#010X};", bank_cfg).unwrap();
+    writeln!(out, "const ITCM_SIZE: u32 = {};", tcm_size(split.itcm)).unwrap();
+    writeln!(out, "const DTCM_SIZE: u32 = {};", tcm_size(split.dtcm)).unwrap();
+    out
+}
+
+/// The `.text` placement for the flash section of the linker script
+pub fn text() -> &'static str {
+    if env::var_os("CARGO_FEATURE_ITCM_TEXT").is_none() {
+        "*(.text*)\n"
+    } else {
+        "/* itcm-text: all code runs from .fastrun */\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_split() {
+        let split = check_split(128, 128).unwrap();
+        assert_eq!(
+            split,
+            Split {
+                itcm: 128,
+                dtcm: 128,
+                ocram: 256
+            }
+        );
+        assert_eq!(
+            config(&split),
+            "// 128K ITCM, 128K DTCM, 256K OCRAM\n\
+             const FLEXRAM_BANK_CFG: u32 = 0x5555AAFF;\n\
+             const ITCM_SIZE: u32 = 8;\n\
+             const DTCM_SIZE: u32 = 8;\n"
+        );
+    }
+
+    #[test]
+    fn no_itcm() {
+        let split = check_split(0, 256).unwrap();
+        assert_eq!(split.ocram, 256);
+        assert_eq!(
+            config(&split),
+            "// 0K ITCM, 256K DTCM, 256K OCRAM\n\
+             const FLEXRAM_BANK_CFG: u32 = 0x5555AAAA;\n\
+             const ITCM_SIZE: u32 = 0;\n\
+             const DTCM_SIZE: u32 = 9;\n"
+        );
+    }
+
+    #[test]
+    fn memory_block() {
+        let memory = memory(&check_split(64, 256).unwrap());
+        assert!(memory.contains("ITCM (rwx):  ORIGIN = 0x00000000, LENGTH = 64K\n"));
+        assert!(memory.contains("DTCM (rwx):  ORIGIN = 0x20000000, LENGTH = 256K\n"));
+        assert!(memory.contains("OCRAM (rwx): ORIGIN = 0x20280000, LENGTH = 192K\n"));
+    }
+
+    #[test]
+    fn rejected_splits() {
+        assert_eq!(
+            check_split(48, 128).unwrap_err(),
+            "TEENSY40_ITCM_KB is 48K, but the TCMs can only be 0K or a power of two from 32K up"
+        );
+        assert_eq!(
+            check_split(128, 16).unwrap_err(),
+            "TEENSY40_DTCM_KB is 16K, but the TCMs can only be 0K or a power of two from 32K up"
+        );
+        assert_eq!(
+            check_split(128, 0).unwrap_err(),
+            "TEENSY40_DTCM_KB is 0K, but the DTCM holds .data, .bss and the stack"
+        );
+        assert_eq!(
+            check_split(512, 256).unwrap_err(),
+            "512K of ITCM and 256K of DTCM is more than the 512K of FlexRAM"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Interrupt table handling
+//!
+//! The table has one row per RT1062 interrupt, listing its IRQ number,
+//! the name of its handler, the name of its `Interrupt` variant and a
+//! short description. Reserved IRQs are left out. From it we generate
+//! the `interrupts!` invocation in `interrupt`, and the linker script
+//! lines which point each handler at `DefaultHandler` unless the
+//! program defines one.
+
+use std::{collections::HashSet, fmt::Write};
+
+/// A single interrupt
+pub struct Interrupt {
+    pub irq: u32,
+    pub handler: String,
+    pub variant: String,
+    pub description: String,
+}
+
+/// The number of external interrupts wired to the NVIC
+const IRQS: u32 = 160;
+
+/// Parse the table
+pub fn parse(table: &str) -> Vec<Interrupt> {
+    let mut interrupts = Vec::new();
+    for (line_number, line) in table.lines().enumerate().skip(1) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.splitn(4, ',').map(str::trim).collect();
+        if fields.len() != 4 {
+            panic!(
+                "interrupts.csv:{}: expected 4 columns, found {}",
+                line_number + 1,
+                fields.len()
+            );
+        }
+        interrupts.push(Interrupt {
+            irq: fields[0].parse().unwrap_or_else(|_| {
+                panic!(
+                    "interrupts.csv:{}: bad number {}",
+                    line_number + 1,
+                    fields[0]
+                )
+            }),
+            handler: fields[1].to_string(),
+            variant: fields[2].to_string(),
+            description: fields[3].to_string(),
+        });
+    }
+    interrupts
+}
+
+/// Check the table for mistakes which would shift the vector table
+///
+/// The table is checked against the reference manual by the tests
+/// below, which run on the host through `build/tests`.
+pub fn validate(interrupts: &[Interrupt]) {
+    let mut handlers = HashSet::new();
+    let mut variants = HashSet::new();
+    for (i, interrupt) in interrupts.iter().enumerate() {
+        if interrupt.irq >= IRQS {
+            panic!(
+                "{} has IRQ {}, but there are only {}",
+                interrupt.handler, interrupt.irq, IRQS
+            );
+        }
+        if i > 0 && interrupts[i - 1].irq >= interrupt.irq {
+            panic!(
+                "IRQ {} ({}) is out of order",
+                interrupt.irq, interrupt.handler
+            );
+        }
+        if !is_identifier(&interrupt.handler) || !is_identifier(&interrupt.variant) {
+            panic!("IRQ {} has a bad handler or variant name", interrupt.irq);
+        }
+        if !interrupt
+            .variant
+            .starts_with(|c: char| c.is_ascii_uppercase())
+        {
+            panic!("{} should be in CamelCase", interrupt.variant);
+        }
+        if !handlers.insert(&interrupt.handler) {
+            panic!("{} is listed twice", interrupt.handler);
+        }
+        if !variants.insert(&interrupt.variant) {
+            panic!("{} is used twice", interrupt.variant);
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The `interrupts!` invocation for `interrupt`
+pub fn interrupts(interrupts: &[Interrupt]) -> String {
+    let mut out = String::new();
+    writeln!(out, "interrupts! {{").unwrap();
+    for interrupt in interrupts {
+        writeln!(out, "    /// {}", interrupt.description).unwrap();
+        writeln!(out, "    ///").unwrap();
+        writeln!(out, "    /// Handled by `{}`", interrupt.handler).unwrap();
+        writeln!(
+            out,
+            "    {} => {}: {},",
+            interrupt.irq, interrupt.handler, interrupt.variant
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// The default handler for each interrupt, for the linker script
+pub fn provides(interrupts: &[Interrupt]) -> String {
+    let mut out = String::new();
+    for interrupt in interrupts {
+        writeln!(out, "PROVIDE({} = DefaultHandler);", interrupt.handler).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entries checked by hand against the i.MX RT1060 reference manual,
+    /// as `(irq, handler)`
+    const KNOWN_ENTRIES: &[(u32, &str)] = &[
+        (0, "DMA0_DMA16"),
+        (20, "LPUART1"),
+        (25, "LPUART6"),
+        (72, "GPIO1_INT0"),
+        (100, "GPT1"),
+        (122, "PIT"),
+        (151, "PWM4_FAULT"),
+        (157, "GPIO6_7_8_9"),
+    ];
+
+    fn table() -> Vec<Interrupt> {
+        parse(include_str!("../src/interrupt/interrupts.csv"))
+    }
+
+    #[test]
+    fn table_is_consistent() {
+        validate(&table());
+    }
+
+    #[test]
+    fn known_entries() {
+        let interrupts = table();
+        for &(irq, handler) in KNOWN_ENTRIES {
+            assert!(
+                interrupts
+                    .iter()
+                    .any(|i| i.irq == irq && i.handler == handler),
+                "{} should be IRQ {}",
+                handler,
+                irq
+            );
+        }
+    }
+}
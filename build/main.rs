@@ -0,0 +1,80 @@
+//! Build script
+//!
+//! This generates the IOMUXC pad types and the Teensy pin numbering
+//! from the pin-mux table in `src/iomuxc/pinmux.csv`, the interrupt
+//! vectors from `src/interrupt/interrupts.csv`, and the linker
+//! script's memory map from the chosen FlexRAM split.
+//!
+//! It also hands `layout.ld` to the linker for any binary using this
+//! crate, along with the optional `memory.x` hook named by
+//! `TEENSY40_MEMORY_X`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+mod flexram;
+mod interrupts;
+mod pinmux;
+
+const PINMUX_TABLE: &str = "src/iomuxc/pinmux.csv";
+const INTERRUPT_TABLE: &str = "src/interrupt/interrupts.csv";
+const LAYOUT: &str = "layout.ld";
+const MEMORY_HOOK_VAR: &str = "TEENSY40_MEMORY_X";
+
+/// The contents of the `memory.x` hook, which `layout.ld` includes
+/// right after the memory map
+fn memory_hook() -> String {
+    match env::var_os(MEMORY_HOOK_VAR) {
+        Some(path) => {
+            println!("cargo:rerun-if-changed={}", Path::new(&path).display());
+            fs::read_to_string(&path).unwrap_or_else(|err| {
+                panic!(
+                    "Could not read {} ({}): {}",
+                    MEMORY_HOOK_VAR,
+                    Path::new(&path).display(),
+                    err
+                )
+            })
+        }
+        None => format!("/* Set {} to add to the memory map */\n", MEMORY_HOOK_VAR),
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", PINMUX_TABLE);
+    println!("cargo:rerun-if-changed={}", INTERRUPT_TABLE);
+    println!("cargo:rerun-if-env-changed={}", flexram::ITCM_VAR);
+    println!("cargo:rerun-if-env-changed={}", flexram::DTCM_VAR);
+    println!("cargo:rerun-if-changed={}", LAYOUT);
+    println!("cargo:rerun-if-env-changed={}", MEMORY_HOOK_VAR);
+
+    let table = fs::read_to_string(PINMUX_TABLE).expect("Could not read the pin-mux table");
+    let pads = pinmux::parse(&table);
+    pinmux::validate(&pads);
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::write(out_dir.join("pins.rs"), pinmux::pins(&pads)).unwrap();
+    fs::write(out_dir.join("signals.rs"), pinmux::signals(&pads)).unwrap();
+    fs::write(out_dir.join("board.rs"), pinmux::board(&pads)).unwrap();
+
+    let table = fs::read_to_string(INTERRUPT_TABLE).expect("Could not read the interrupt table");
+    let irqs = interrupts::parse(&table);
+    interrupts::validate(&irqs);
+    fs::write(out_dir.join("interrupts.rs"), interrupts::interrupts(&irqs)).unwrap();
+    fs::write(out_dir.join("interrupts.x"), interrupts::provides(&irqs)).unwrap();
+
+    let split = flexram::split();
+    fs::write(out_dir.join("memory.ld"), flexram::memory(&split)).unwrap();
+    fs::write(out_dir.join("flexram.rs"), flexram::config(&split)).unwrap();
+    fs::write(out_dir.join("text.ld"), flexram::text()).unwrap();
+
+    fs::copy(LAYOUT, out_dir.join("teensy40.x")).unwrap();
+    fs::write(out_dir.join("memory.x"), memory_hook()).unwrap();
+    println!("cargo:rustc-link-search={}", out_dir.display());
+    // The linker takes any input file it doesn't recognise as a linker
+    // script, so this passes layout.ld on to every binary which links
+    // this crate, without any flags of its own
+    println!("cargo:rustc-link-lib=static:+verbatim,-bundle=teensy40.x");
+}
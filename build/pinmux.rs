@@ -0,0 +1,585 @@
+//! IOMUXC pin-mux table handling
+//!
+//! The table has one row per ALT function of each pad, listing the
+//! pad name, its Teensy pin number, the addresses of its mux and pad
+//! control registers, the ALT number and the peripheral signal. Input
+//! signals which can be routed from more than one pad also list their
+//! `*_SELECT_INPUT` daisy register and the value selecting this pad.
+//! From it we generate the `pads!` invocation in `iomuxc::pin`, the
+//! marker traits in `iomuxc::signal` and the `pins!` invocation in
+//! `board`.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+/// A pad and all of its ALT functions
+pub struct Pad {
+    pub name: String,
+    pub teensy_pin: u32,
+    pub mux: u32,
+    pub pad_ctl: u32,
+    pub alts: Vec<Alt>,
+}
+
+/// A single ALT function of a pad
+pub struct Alt {
+    pub alt: u32,
+    pub signal: String,
+    pub daisy: Option<Daisy>,
+}
+
+/// The input select setting which routes a signal from this pad
+pub struct Daisy {
+    pub register: u32,
+    pub value: u32,
+}
+
+/// The distance between a pad's mux register and its pad control
+/// register. This is the same for every GPIO pad.
+const PAD_CTL_OFFSET: u32 = 0x1F0;
+
+/// The `*_SELECT_INPUT` registers, which all follow the pad control
+/// registers. The ones the RT1060 added over the RT1050, such as those
+/// for `ENET2` and `FLEXCAN3`, come after the `GPIO_SPI_B*` pads at the
+/// end.
+const DAISY_REGISTERS: std::ops::RangeInclusive<u32> = 0x401F_83F4..=0x401F_878C;
+
+/// Parse the table, grouping rows by pad
+pub fn parse(table: &str) -> Vec<Pad> {
+    let mut pads: Vec<Pad> = Vec::new();
+    for (line_number, line) in table.lines().enumerate().skip(1) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 8 {
+            panic!(
+                "pinmux.csv:{}: expected 8 columns, found {}",
+                line_number + 1,
+                fields.len()
+            );
+        }
+        let name = fields[0];
+        let teensy_pin = parse_number(fields[1], line_number);
+        let mux = parse_number(fields[2], line_number);
+        let pad_ctl = parse_number(fields[3], line_number);
+        let alt = Alt {
+            alt: parse_number(fields[4], line_number),
+            signal: fields[5].to_string(),
+            daisy: match (fields[6], fields[7]) {
+                ("", "") => None,
+                (register, value) => Some(Daisy {
+                    register: parse_number(register, line_number),
+                    value: parse_number(value, line_number),
+                }),
+            },
+        };
+
+        match pads.last_mut() {
+            Some(pad) if pad.name == name => {
+                if (pad.teensy_pin, pad.mux, pad.pad_ctl) != (teensy_pin, mux, pad_ctl) {
+                    panic!(
+                        "pinmux.csv:{}: {} disagrees with its earlier rows",
+                        line_number + 1,
+                        name
+                    );
+                }
+                pad.alts.push(alt);
+            }
+            _ => {
+                if pads.iter().any(|pad| pad.name == name) {
+                    panic!(
+                        "pinmux.csv:{}: rows for {} must be contiguous",
+                        line_number + 1,
+                        name
+                    );
+                }
+                pads.push(Pad {
+                    name: name.to_string(),
+                    teensy_pin,
+                    mux,
+                    pad_ctl,
+                    alts: vec![alt],
+                });
+            }
+        }
+    }
+    pads
+}
+
+fn parse_number(field: &str, line_number: usize) -> u32 {
+    let parsed = match field.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => field.parse(),
+    };
+    parsed.unwrap_or_else(|_| panic!("pinmux.csv:{}: bad number {}", line_number + 1, field))
+}
+
+/// Check the table for internal consistency
+///
+/// The table is checked against the reference manual by the tests
+/// below, which run on the host through `build/tests`.
+pub fn validate(pads: &[Pad]) {
+    let mut teensy_pins = BTreeMap::new();
+    let mut muxes = BTreeMap::new();
+    for pad in pads {
+        if let Some(other) = teensy_pins.insert(pad.teensy_pin, &pad.name) {
+            panic!(
+                "{} and {} are both Teensy pin {}",
+                other, pad.name, pad.teensy_pin
+            );
+        }
+        if let Some(other) = muxes.insert(pad.mux, &pad.name) {
+            panic!("{} and {} share a mux register", other, pad.name);
+        }
+        if pad.pad_ctl != pad.mux + PAD_CTL_OFFSET {
+            panic!("{} has mismatched mux and pad control registers", pad.name);
+        }
+
+        let mut methods = BTreeMap::new();
+        for alt in &pad.alts {
+            if alt.alt > 9 {
+                panic!("{} has no ALT{}", pad.name, alt.alt);
+            }
+            if let Some(other) = methods.insert(method(&alt.signal), alt.alt) {
+                panic!(
+                    "{} ALT{} and ALT{} both convert with {}",
+                    pad.name,
+                    other,
+                    alt.alt,
+                    method(&alt.signal)
+                );
+            }
+        }
+        let is_gpio = |alt: &Alt| {
+            let (base, instance, rest) = split_signal(&alt.signal);
+            base == "GPIO"
+                && ["1", "2", "3", "4"].contains(&instance)
+                && rest.len() == 1
+                && matches!(
+                    rest[0].strip_prefix("IO").map(str::parse::<u32>),
+                    Some(Ok(bit)) if bit < 32
+                )
+        };
+        if !pad.alts.iter().any(|alt| alt.alt == 5 && is_gpio(alt)) {
+            panic!("{} has no GPIO1-4 function on ALT5", pad.name);
+        }
+    }
+
+    if !teensy_pins.keys().copied().eq(0..40) {
+        panic!("The table must cover exactly Teensy pins 0 through 39");
+    }
+    if let Err(err) = check_daisies(pads) {
+        panic!("{}", err);
+    }
+}
+
+/// Check that each daisy register belongs to a single signal, and
+/// that no two pads select that signal with the same value
+fn check_daisies(pads: &[Pad]) -> Result<(), String> {
+    let mut daisy_signals = BTreeMap::new();
+    let mut daisy_values = BTreeMap::new();
+    for pad in pads {
+        for alt in &pad.alts {
+            let daisy = match &alt.daisy {
+                Some(daisy) => daisy,
+                None => continue,
+            };
+            if !DAISY_REGISTERS.contains(&daisy.register) || daisy.register % 4 != 0 {
+                return Err(format!(
+                    "{} {} has a bad daisy register {}",
+                    pad.name,
+                    alt.signal,
+                    address(daisy.register)
+                ));
+            }
+            if daisy.value > 7 {
+                return Err(format!("{} {} has a bad daisy value", pad.name, alt.signal));
+            }
+            let signal = daisy_signals.entry(daisy.register).or_insert(&alt.signal);
+            if *signal != &alt.signal {
+                return Err(format!(
+                    "{} and {} share the daisy register {}",
+                    signal,
+                    alt.signal,
+                    address(daisy.register)
+                ));
+            }
+            if let Some(other) = daisy_values.insert((daisy.register, daisy.value), &pad.name) {
+                return Err(format!(
+                    "{} and {} both select {} with daisy value {}",
+                    other, pad.name, alt.signal, daisy.value
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Generate the `pads!` invocation for `iomuxc::pin`
+pub fn pins(pads: &[Pad]) -> String {
+    let mut out = String::from("pads! {\n");
+    for pad in pads {
+        let pad_type = pad_type(&pad.name);
+        writeln!(out, "    /// `{}`, Teensy pin {}", pad.name, pad.teensy_pin).unwrap();
+        let (bank, bit) = gpio(pad);
+        writeln!(
+            out,
+            "    {} = {}_INIT, {}, {}, {}[{}] {{",
+            pad_type,
+            pad.name,
+            address(pad.mux),
+            address(pad.pad_ctl),
+            bank,
+            bit
+        )
+        .unwrap();
+        for alt in &pad.alts {
+            write!(
+                out,
+                "        {} => {} -> {}{}: {}, \"`{}` (ALT{})\",",
+                alt.alt,
+                method(&alt.signal),
+                pad_type,
+                muxed_suffix(&alt.signal),
+                trait_path(&alt.signal),
+                alt.signal,
+                alt.alt
+            )
+            .unwrap();
+            if let Some(daisy) = &alt.daisy {
+                write!(out, " daisy {} = {},", address(daisy.register), daisy.value).unwrap();
+            }
+            out.push('\n');
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Generate the marker traits for `iomuxc::signal`
+pub fn signals(pads: &[Pad]) -> String {
+    let mut signals = BTreeMap::new();
+    for alt in pads.iter().flat_map(|pad| &pad.alts) {
+        if !has_driver(&alt.signal) {
+            signals.insert(trait_name(&alt.signal), &alt.signal);
+        }
+    }
+
+    let mut out = String::new();
+    for (name, signal) in signals {
+        writeln!(out, "/// `{}`", signal).unwrap();
+        writeln!(out, "pub trait {} {{}}", name).unwrap();
+    }
+    out
+}
+
+/// The GPIO bank and bit of a pad, from its ALT5 function. `GPIO2_IO03`
+/// becomes `("Gpio2", 3)`.
+fn gpio(pad: &Pad) -> (String, u32) {
+    let signal = &pad
+        .alts
+        .iter()
+        .find(|alt| alt.alt == 5)
+        .expect("Every pad has a GPIO function")
+        .signal;
+    let (base, instance, rest) = split_signal(signal);
+    let bit = rest[0].trim_start_matches("IO").parse().unwrap();
+    (format!("{}{}", camel(base), instance), bit)
+}
+
+/// The Teensy pin wired to the orange LED
+const LED_PIN: u32 = 13;
+
+/// Generate the `pins!` invocation for `board`
+pub fn board(pads: &[Pad]) -> String {
+    let mut pads: Vec<&Pad> = pads.iter().collect();
+    pads.sort_by_key(|pad| pad.teensy_pin);
+
+    let mut out = String::from("pins! {\n");
+    for pad in pads {
+        let field = if pad.teensy_pin == LED_PIN {
+            writeln!(
+                out,
+                "    /// Teensy pin {}, wired to the orange LED",
+                pad.teensy_pin
+            )
+            .unwrap();
+            String::from("led")
+        } else {
+            writeln!(out, "    /// Teensy pin {}", pad.teensy_pin).unwrap();
+            format!("p{}", pad.teensy_pin)
+        };
+        writeln!(
+            out,
+            "    {}: P{} = {},",
+            field,
+            pad.teensy_pin,
+            pad_type(&pad.name)
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn address(address: u32) -> String {
+    format!("0x{:04X}_{:04X}", address >> 16, address & 0xFFFF)
+}
+
+/// Peripherals whose marker traits live with their driver rather than
+/// in `iomuxc::signal`
+fn has_driver(signal: &str) -> bool {
+    let (base, _, rest) = split_signal(signal);
+    base == "LPUART" && (rest == ["TX"] || rest == ["RX"])
+}
+
+fn trait_path(signal: &str) -> String {
+    if has_driver(signal) {
+        format!("super::super::lpuart::{}", trait_name(signal))
+    } else {
+        format!("super::signal::{}", trait_name(signal))
+    }
+}
+
+/// `GPIO_AD_B0_02` becomes `GpioAdB0_02`
+fn pad_type(pad: &str) -> String {
+    join(pad.split('_').map(camel))
+}
+
+/// `LPUART6_TX` becomes `into_lpuart_tx`
+fn method(signal: &str) -> String {
+    let stem: Vec<String> = stem(signal).iter().map(|t| t.to_lowercase()).collect();
+    format!("into_{}", stem.join("_"))
+}
+
+/// `LPUART6_TX` becomes `LpUartTx`, to be appended to the pad type
+fn muxed_suffix(signal: &str) -> String {
+    let stem = stem(signal);
+    let (base, instance) = split_instance(&stem[0]);
+    let head = format!("{}{}", peripheral(base), instance);
+    join(std::iter::once(head).chain(stem[1..].iter().map(|t| camel(t))))
+}
+
+/// `LPUART6_TX` becomes `LpUart6Tx`
+fn trait_name(signal: &str) -> String {
+    let (base, instance, rest) = split_signal(signal);
+    let head = format!("{}{}", peripheral(base), instance);
+    join(std::iter::once(head).chain(rest.into_iter().map(camel)))
+}
+
+/// The parts of a signal name used for its conversion method. The
+/// peripheral instance and any trailing signal index are dropped,
+/// since a pad rarely has two functions which differ only by them.
+fn stem(signal: &str) -> Vec<String> {
+    let (base, instance, rest) = split_signal(signal);
+    if base == "GPIO" {
+        return vec![base.to_string()];
+    }
+    // FlexIO signals are named `FLEXIOn_FLEXIOmm`, and a pad can carry
+    // two FlexIO instances, so keep the instance for them.
+    if rest.len() == 1 && split_instance(rest[0]).0 == base {
+        return vec![format!("{}{}", base, instance)];
+    }
+
+    let mut stem = vec![base.to_string()];
+    stem.extend(rest.iter().map(|t| t.to_string()));
+    let last = stem.last_mut().unwrap();
+    let (name, index) = split_instance(last);
+    if !name.is_empty() && !index.is_empty() && name.chars().all(|c| c.is_ascii_uppercase()) {
+        *last = name.to_string();
+    }
+    stem
+}
+
+/// Split `LPUART6_TX` into `("LPUART", "6", ["TX"])`
+fn split_signal(signal: &str) -> (&str, &str, Vec<&str>) {
+    let mut tokens = signal.split('_');
+    let (base, instance) = split_instance(tokens.next().unwrap());
+    (base, instance, tokens.collect())
+}
+
+/// Split `LPUART6` into `("LPUART", "6")`
+fn split_instance(token: &str) -> (&str, &str) {
+    let base = token.trim_end_matches(|c: char| c.is_ascii_digit());
+    (base, &token[base.len()..])
+}
+
+/// Convert a single upper-case token to camel case, using the
+/// peripheral spellings from [`peripheral`] where they apply
+fn camel(token: &str) -> String {
+    let (base, index) = split_instance(token);
+    if let Some(name) = known_peripheral(base) {
+        return format!("{}{}", name, index);
+    }
+    let mut chars = token.chars();
+    let first = chars.next().unwrap();
+    first.to_string() + &chars.as_str().to_lowercase()
+}
+
+/// Concatenate camel-case parts, separating adjacent digits with an
+/// underscore so that `B0` and `02` don't run together
+fn join<I: IntoIterator<Item = String>>(parts: I) -> String {
+    let mut out = String::new();
+    for part in parts {
+        let digit_boundary = out.ends_with(|c: char| c.is_ascii_digit())
+            && part.starts_with(|c: char| c.is_ascii_digit());
+        if digit_boundary {
+            out.push('_');
+        }
+        out.push_str(&part);
+    }
+    out
+}
+
+fn peripheral(base: &str) -> &'static str {
+    known_peripheral(base)
+        .unwrap_or_else(|| panic!("No camel-case spelling for peripheral {}", base))
+}
+
+fn known_peripheral(base: &str) -> Option<&'static str> {
+    Some(match base {
+        "ACMP" => "Acmp",
+        "ARM" => "Arm",
+        "CCM" => "Ccm",
+        "CSI" => "Csi",
+        "ENET" => "Enet",
+        "EWM" => "Ewm",
+        "FLEXCAN" => "FlexCan",
+        "FLEXIO" => "FlexIo",
+        "FLEXPWM" => "FlexPwm",
+        "FLEXSPIA" => "FlexSpiA",
+        "FLEXSPIB" => "FlexSpiB",
+        "GPIO" => "Gpio",
+        "GPT" => "Gpt",
+        "KPP" => "Kpp",
+        "LCD" => "Lcd",
+        "LPI2C" => "LpI2c",
+        "LPSPI" => "LpSpi",
+        "LPUART" => "LpUart",
+        "MQS" => "Mqs",
+        "NMI" => "Nmi",
+        "QTIMER" => "QTimer",
+        "REF" => "Ref",
+        "SAI" => "Sai",
+        "SEMC" => "Semc",
+        "SPDIF" => "Spdif",
+        "SRC" => "Src",
+        "USB" => "Usb",
+        "USDHC" => "Usdhc",
+        "WDOG" => "Wdog",
+        "XBAR" => "Xbar",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entries checked by hand against the i.MX RT1060 reference manual,
+    /// as `(pad, mux, pad_ctl, alt, signal)`. These catch a table which has
+    /// been shifted or mis-transcribed.
+    const KNOWN_ENTRIES: &[(&str, u32, u32, u32, &str)] = &[
+        ("GPIO_EMC_04", 0x401F_8024, 0x401F_8214, 5, "GPIO4_IO04"),
+        ("GPIO_AD_B0_02", 0x401F_80C4, 0x401F_82B4, 2, "LPUART6_TX"),
+        ("GPIO_AD_B0_03", 0x401F_80C8, 0x401F_82B8, 2, "LPUART6_RX"),
+        ("GPIO_AD_B1_00", 0x401F_80FC, 0x401F_82EC, 3, "LPI2C1_SCL"),
+        ("GPIO_AD_B1_01", 0x401F_8100, 0x401F_82F0, 3, "LPI2C1_SDA"),
+        ("GPIO_B0_00", 0x401F_813C, 0x401F_832C, 3, "LPSPI4_PCS0"),
+        ("GPIO_B0_03", 0x401F_8148, 0x401F_8338, 5, "GPIO2_IO03"),
+        ("GPIO_B1_00", 0x401F_817C, 0x401F_836C, 2, "LPUART4_TX"),
+        ("GPIO_SD_B0_00", 0x401F_81BC, 0x401F_83AC, 5, "GPIO3_IO12"),
+    ];
+
+    /// Daisy settings checked by hand against the reference manual, as
+    /// `(pad, signal, register, value)`
+    const KNOWN_DAISIES: &[(&str, &str, u32, u32)] = &[
+        ("GPIO_AD_B0_02", "LPUART6_TX", 0x401F_8554, 1),
+        ("GPIO_AD_B0_03", "LPUART6_RX", 0x401F_8550, 1),
+        ("GPIO_AD_B1_00", "LPI2C1_SCL", 0x401F_84CC, 1),
+        ("GPIO_B0_01", "LPSPI4_SDI", 0x401F_8524, 0),
+        ("GPIO_B1_01", "LPUART4_RX", 0x401F_8540, 2),
+        ("GPIO_B0_03", "FLEXCAN1_RX", 0x401F_844C, 3),
+        ("GPIO_AD_B1_09", "FLEXCAN1_RX", 0x401F_844C, 2),
+        ("GPIO_EMC_04", "XBAR1_INOUT06", 0x401F_861C, 0),
+        ("GPIO_SD_B0_02", "XBAR1_INOUT06", 0x401F_861C, 1),
+        ("GPIO_EMC_37", "FLEXCAN3_RX", 0x401F_878C, 0),
+    ];
+
+    fn table() -> Vec<Pad> {
+        parse(include_str!("../src/iomuxc/pinmux.csv"))
+    }
+
+    #[test]
+    fn table_is_consistent() {
+        validate(&table());
+    }
+
+    #[test]
+    fn known_entries() {
+        let pads = table();
+        for &(name, mux, pad_ctl, alt, signal) in KNOWN_ENTRIES {
+            let pad = pads.iter().find(|pad| pad.name == name);
+            let pad = pad.unwrap_or_else(|| panic!("{} is missing", name));
+            assert_eq!(
+                (pad.mux, pad.pad_ctl),
+                (mux, pad_ctl),
+                "{} has the wrong register addresses",
+                name
+            );
+            assert!(
+                pad.alts
+                    .iter()
+                    .any(|entry| entry.alt == alt && entry.signal == signal),
+                "{} ALT{} should be {}",
+                name,
+                alt,
+                signal
+            );
+        }
+    }
+
+    #[test]
+    fn known_daisies() {
+        let pads = table();
+        for &(name, signal, register, value) in KNOWN_DAISIES {
+            let daisy = pads
+                .iter()
+                .filter(|pad| pad.name == name)
+                .flat_map(|pad| &pad.alts)
+                .find(|alt| alt.signal == signal)
+                .and_then(|alt| alt.daisy.as_ref())
+                .unwrap_or_else(|| panic!("{} {} has no daisy setting", name, signal));
+            assert_eq!(
+                (daisy.register, daisy.value),
+                (register, value),
+                "{} {} has the wrong daisy setting",
+                name,
+                signal
+            );
+        }
+    }
+
+    #[test]
+    fn daisy_values_are_unique() {
+        assert_eq!(check_daisies(&table()), Ok(()));
+    }
+
+    #[test]
+    fn duplicate_daisy_value_is_caught() {
+        let pads = parse(
+            "pad,teensy_pin,mux,pad_ctl,alt,signal,daisy,daisy_value\n\
+             GPIO_AD_B0_03,0,0x401F80C8,0x401F82B8,2,LPUART6_RX,0x401F8550,1\n\
+             GPIO_EMC_26,1,0x401F8080,0x401F8270,2,LPUART6_RX,0x401F8550,1\n",
+        );
+        assert_eq!(
+            check_daisies(&pads),
+            Err(String::from(
+                "GPIO_AD_B0_03 and GPIO_EMC_26 both select LPUART6_RX with daisy value 1"
+            ))
+        );
+    }
+}
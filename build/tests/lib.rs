@@ -0,0 +1,14 @@
+//! Host tests for the build script
+//!
+//! Cargo never runs the tests of a build script, so this crate pulls
+//! in its modules to do so. Run them with `make test`, or `cargo test`
+//! from this directory.
+
+#![allow(dead_code)]
+
+#[path = "../flexram.rs"]
+mod flexram;
+#[path = "../interrupts.rs"]
+mod interrupts;
+#[path = "../pinmux.rs"]
+mod pinmux;
@@ -1,7 +1,6 @@
 #![no_builtins]
 #![no_main]
 #![no_std]
-#![feature(asm)]
 
 extern crate teensy40;
 use teensy40::*;
@@ -15,10 +14,8 @@ pub extern "C" fn main() {
     uart_clock.set_divisor(1);
 
     let iomux = ccm.enable::<iomuxc::Iomuxc>().unwrap();
-    let tx_pin = iomux
-        .get_pin::<iomuxc::pin::GpioAdB0_02>()
-        .unwrap()
-        .into_lpuart_tx();
+    let pins = board::Pins::new(&iomux).unwrap();
+    let tx_pin = pins.p1.into_lpuart_tx();
 
     let mut uart = ccm.enable::<lpuart::Uart6>().unwrap();
     uart.set_clocks(250, 10);
@@ -30,19 +27,17 @@ pub extern "C" fn main() {
     // Sleep forever
     loop {
         unsafe {
-            asm!("wfi" :::: "volatile");
+            core::arch::asm!("wfi");
         }
     }
 }
 
 #[panic_handler]
 fn teensy_panic(_: &core::panic::PanicInfo) -> ! {
-    // Enable the pin
     unsafe {
-        debug::enable();
-        debug::led();
+        debug::steal_led().on();
         loop {
-            asm!("wfi" :::: "volatile");
+            core::arch::asm!("wfi");
         }
     }
 }
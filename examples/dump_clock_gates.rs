@@ -1,7 +1,6 @@
 #![no_builtins]
 #![no_main]
 #![no_std]
-#![feature(asm)]
 
 extern crate teensy40;
 use teensy40::*;
@@ -171,19 +170,17 @@ pub extern "C" fn main() {
     // Sleep forever
     loop {
         unsafe {
-            asm!("wfi" :::: "volatile");
+            core::arch::asm!("wfi");
         }
     }
 }
 
 #[panic_handler]
 fn teensy_panic(_: &core::panic::PanicInfo) -> ! {
-    // Enable the pin
     unsafe {
-        debug::enable();
-        debug::led();
+        debug::steal_led().on();
         loop {
-            asm!("wfi" :::: "volatile");
+            core::arch::asm!("wfi");
         }
     }
 }
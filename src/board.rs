@@ -0,0 +1,71 @@
+//! Teensy pin numbering
+//!
+//! The pads of the i.MX RT1062 are named after their pad group, such
+//! as `GPIO_AD_B0_02`, but the Teensy labels them with pin numbers.
+//! [`Pins`] takes every pad broken out on the Teensy 4.0 at once, and
+//! names them by those numbers:
+//!
+//! ```ignore
+//! let pins = board::Pins::new(&iomuxc).unwrap();
+//! let tx = pins.p1.into_lpuart_tx();
+//! ```
+//!
+//! Each field has the same type as the underlying pad in
+//! [`iomuxc::pin`](../iomuxc/pin/index.html), so the `into_*`
+//! conversions and their typestate work just as they do there. The
+//! type aliases `P0` through `P39` name those types by pin number.
+
+use super::iomuxc::{pin::*, Iomuxc, Pin, PinError};
+
+macro_rules! pins {
+    ($(
+        $(#[$meta:meta])*
+        $field:ident: $alias:ident = $pad:ident,
+    )*) => {
+        $(
+            $(#[$meta])*
+            pub type $alias = $pad;
+        )*
+
+        /// Every pin on the Teensy 4.0
+        pub struct Pins {
+            $(
+                $(#[$meta])*
+                pub $field: $alias,
+            )*
+        }
+
+        impl Pins {
+            /// Take every pin
+            ///
+            /// # Errors
+            /// Returns [`PinError::InUse`] if any of the pins has
+            /// already been taken. In that case none of the pins are
+            /// kept.
+            pub fn new(iomuxc: &Iomuxc) -> Result<Pins, PinError> {
+                let pins = ($(iomuxc.get_pin::<$alias>(),)*);
+                match pins {
+                    ($(Ok($field),)*) => Ok(Pins { $($field,)* }),
+                    ($($field,)*) => {
+                        $(
+                            if let Ok(pin) = $field {
+                                pin.release();
+                            }
+                        )*
+                        Err(PinError::InUse)
+                    }
+                }
+            }
+
+            /// Return every pin to the pool
+            pub fn release(self) {
+                $(self.$field.release();)*
+            }
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/board.rs"));
+
+/// The pin wired to the orange LED
+pub type Led = P13;
@@ -2,17 +2,57 @@
 // readable as transmutes
 #![allow(clippy::transmute_ptr_to_ptr)]
 
-use super::startup::startup;
+use super::{
+    interrupt::{self, Handler},
+    startup::startup,
+};
 use core::mem::transmute;
 
 extern "C" {
     fn _boot_stack();
     static _image_length: core::ffi::c_void;
+
+    fn NonMaskableInt();
+    fn HardFault();
+    fn MemoryManagement();
+    fn BusFault();
+    fn UsageFault();
+    fn SVCall();
+    fn DebugMonitor();
+    fn PendSV();
+    fn SysTick();
+}
+
+/// The vector table, which `VTOR` must be aligned to the size of
+#[repr(C, align(1024))]
+pub struct VectorTable {
+    exceptions: [Option<Handler>; 16],
+    interrupts: [Option<Handler>; 160],
 }
 
 #[link_section = ".vectors"]
 #[no_mangle]
-pub static _VECTOR_TABLE: [unsafe extern "C" fn(); 2] = [_boot_stack, startup];
+pub static _VECTOR_TABLE: VectorTable = VectorTable {
+    exceptions: [
+        Some(_boot_stack),
+        Some(startup),
+        Some(NonMaskableInt),
+        Some(HardFault),
+        Some(MemoryManagement),
+        Some(BusFault),
+        Some(UsageFault),
+        None,
+        None,
+        None,
+        None,
+        Some(SVCall),
+        Some(DebugMonitor),
+        None,
+        Some(PendSV),
+        Some(SysTick),
+    ],
+    interrupts: interrupt::VECTORS,
+};
 
 #[link_section = ".bootdata"]
 #[no_mangle]
@@ -0,0 +1,119 @@
+//! The Cortex-M7 L1 caches
+//!
+//! Startup turns on the 32K instruction and data caches, which cover
+//! flash and OCRAM. The TCMs are never cached. Nothing keeps the data
+//! cache coherent with DMA, so a DMA user must clean a buffer before
+//! the DMA reads it, and invalidate it before the CPU reads what the
+//! DMA wrote:
+//!
+//! ```ignore
+//! cache::clean_dcache(buffer.as_ptr() as usize, buffer.len());
+//! // ... start the transfer out of buffer ...
+//!
+//! // ... after a transfer into buffer ...
+//! unsafe {
+//!     cache::invalidate_dcache(buffer.as_ptr() as usize, buffer.len());
+//! }
+//! ```
+//!
+//! Maintenance works on whole 32-byte lines, so DMA buffers are best
+//! aligned to, and sized in, whole lines.
+
+use core::arch::asm;
+use core::ptr::{read_volatile, write_volatile};
+
+/// The size of a cache line, in bytes
+pub const LINE_SIZE: usize = 32;
+
+/// `SCB_CCR`: Configuration and Control Register
+const CCR: *mut u32 = 0xE000_ED14 as *mut u32;
+/// `CCSIDR`: Cache Size ID Register
+const CCSIDR: *const u32 = 0xE000_ED80 as *const u32;
+/// `CSSELR`: Cache Size Selection Register
+const CSSELR: *mut u32 = 0xE000_ED84 as *mut u32;
+/// `ICIALLU`: Invalidate all instruction caches to PoU
+const ICIALLU: *mut u32 = 0xE000_EF50 as *mut u32;
+/// `DCIMVAC`: Invalidate data cache line by address to PoC
+const DCIMVAC: *mut u32 = 0xE000_EF5C as *mut u32;
+/// `DCISW`: Invalidate data cache line by set and way
+const DCISW: *mut u32 = 0xE000_EF60 as *mut u32;
+/// `DCCMVAC`: Clean data cache line by address to PoC
+const DCCMVAC: *mut u32 = 0xE000_EF68 as *mut u32;
+/// `DCCIMVAC`: Clean and invalidate data cache line by address to PoC
+const DCCIMVAC: *mut u32 = 0xE000_EF70 as *mut u32;
+
+/// `CCR.DC`: data cache enable
+const CCR_DC: u32 = 1 << 16;
+/// `CCR.IC`: instruction cache enable
+const CCR_IC: u32 = 1 << 17;
+
+/// Invalidate and turn on both caches
+///
+/// # Safety
+/// The caches come out of reset holding garbage, so this must run
+/// before anything could have been cached, and only once.
+pub(crate) unsafe fn enable() {
+    write_volatile(ICIALLU, 0);
+
+    // Select the L1 data cache, and invalidate every line of it
+    write_volatile(CSSELR, 0);
+    asm!("dsb");
+    let ccsidr = read_volatile(CCSIDR);
+    let sets = (ccsidr >> 13 & 0x7FFF) + 1;
+    let ways = (ccsidr >> 3 & 0x3FF) + 1;
+    let set_shift = (ccsidr & 7) + 4;
+    let way_shift = (ways - 1).leading_zeros();
+    for set in 0..sets {
+        for way in 0..ways {
+            write_volatile(DCISW, way << way_shift | set << set_shift);
+        }
+    }
+    asm!("dsb");
+
+    write_volatile(CCR, read_volatile(CCR) | CCR_DC | CCR_IC);
+    asm!("dsb", "isb");
+}
+
+/// Write one maintenance register for each line of a range
+fn for_each_line(register: *mut u32, address: usize, length: usize) {
+    if length == 0 {
+        return;
+    }
+    let mut line = address & !(LINE_SIZE - 1);
+    let end = address + length;
+    unsafe {
+        asm!("dsb");
+        while line < end {
+            write_volatile(register, line as u32);
+            line += LINE_SIZE;
+        }
+        asm!("dsb", "isb");
+    }
+}
+
+/// Write any cached changes in a range back to memory
+///
+/// Call this before a DMA transfer reads from the range.
+pub fn clean_dcache(address: usize, length: usize) {
+    for_each_line(DCCMVAC, address, length);
+}
+
+/// Write any cached changes in a range back to memory, then drop it
+/// from the cache
+pub fn clean_invalidate_dcache(address: usize, length: usize) {
+    for_each_line(DCCIMVAC, address, length);
+}
+
+/// Drop a range from the cache, so that the next read comes from
+/// memory
+///
+/// Call this after a DMA transfer writes to the range, and before
+/// reading it.
+///
+/// # Safety
+/// Any cached writes to the range are lost. That includes writes to
+/// anything else sharing the first or last cache line, so the range
+/// should be aligned to [`LINE_SIZE`] at both ends.
+pub unsafe fn invalidate_dcache(address: usize, length: usize) {
+    for_each_line(DCIMVAC, address, length);
+}
@@ -4,80 +4,240 @@
 //! various clock selection muxes and all the individual device clock
 //! gates.
 
-use bit_field::BitField;
+use super::register::{Field, ReadOnly, ReadWrite, Segmented};
 use core::{
+    cell::Cell,
     ops::{Deref, DerefMut},
-    sync::atomic::{AtomicBool, Ordering},
 };
-use volatile::{ReadOnly, Volatile};
+use critical_section::Mutex;
 
-#[repr(C, packed)]
+register! {
+    /// `CCR`: CCM Control Register
+    ccr {}
+
+    /// `CSR`: CCM Status Register
+    csr {}
+
+    /// `CCSR`: CCM Clock Switcher Register
+    ccsr {}
+
+    /// `CACRR`: CCM Arm Clock Root Register
+    cacrr {
+        ARM_PODF: Field = 0..3,
+    }
+
+    /// `CBCDR`: CCM Bus Clock Divider Register
+    cbcdr {
+        AHB_PODF: Field = 10..13,
+        PERIPH_CLK_SEL: Field<PeriphClockInput> = 25..26,
+        PERIPH_CLK2_PODF: Field = 27..30,
+    }
+
+    /// `CBCMR`: CCM Bus Clock Multiplexer Register
+    cbcmr {
+        PERIPH_CLK2_SEL: Field<PeriphClock2Input> = 12..14,
+        PRE_PERIPH_CLK_SEL: Field<PrePeriphClockInput> = 18..20,
+    }
+
+    /// `CSCMR1`: CCM Serial Clock Multiplexer Register 1
+    cscmr1 {}
+
+    /// `CSCMR2`: CCM Serial Clock Multiplexer Register 2
+    cscmr2 {}
+
+    /// `CSCDR1`: CCM Serial Clock Divider Register 1
+    cscdr1 {
+        UART_CLK_PODF: Field = 0..6,
+        UART_CLK_SEL: Field<UartClockInput> = 6..7,
+    }
+
+    /// `CS1CDR`: CCM Clock Divider Register
+    cs1cdr {}
+
+    /// `CS2CDR`: CCM Clock Divider Register
+    cs2cdr {}
+
+    /// `CDCDR`: CCM D1 Clock Divider Register
+    cdcdr {}
+
+    /// `CSCDR2`: CCM Serial Clock Divider Register 2
+    cscdr2 {}
+
+    /// `CSCDR3`: CCM Serial Clock Divider Register 3
+    cscdr3 {}
+
+    /// `CDHIPR`: CCM Divider Handshake In-Process Register
+    cdhipr {
+        PERIPH2_CLK_SEL_BUSY: Bit = 3,
+        PERIPH_CLK_SEL_BUSY: Bit = 5,
+    }
+
+    /// `CLPCR`: CCM Low Power Control Register
+    clpcr {}
+
+    /// `CISR`: CCM Interrupt Status Register
+    cisr {}
+
+    /// `CIMR`: CCM Interrupt Mask Register
+    cimr {}
+
+    /// `CCOSR`: CCM Clock Output Source Register
+    ccosr {}
+
+    /// `CGPR`: CCM General Purpose Register
+    cgpr {}
+
+    /// `CCGR0` through `CCGR7`: CCM Clock Gating Registers
+    ///
+    /// Each register holds 16 two-bit gates. Since gates are looked
+    /// up by index, their fields are built by `gate_field` rather than
+    /// named here.
+    ccgr {}
+
+    /// `CMEOR`: CCM Module Enable Override Register
+    cmeor {}
+}
+
+register! {
+    /// `CCM_ANALOG_PLL_ARM`: Analog ARM PLL control Register
+    pll_arm {
+        DIV_SELECT: Field = 0..7,
+        POWERDOWN: Bit = 12,
+        ENABLE: Bit = 13,
+        BYPASS: Bit = 16,
+    }
+
+    /// `CCM_ANALOG_PLL_USB1`: Analog USB1 480MHz PLL Control Register
+    pll_usb1 {
+        DIV_SELECT: Field<PeripheralPllMultiplier> = 1..2,
+        POWER: Bit = 12,
+        ENABLE: Bit = 13,
+        BYPASS: Bit = 16,
+    }
+
+    /// `CCM_ANALOG_PLL_USB2`: Analog USB2 480MHz PLL Control Register
+    pll_usb2 {}
+
+    /// `CCM_ANALOG_PLL_SYS`: Analog System PLL Control Register
+    pll_sys {
+        DIV_SELECT: Field<PeripheralPllMultiplier> = 0..1,
+        BYPASS: Bit = 16,
+    }
+
+    /// `CCM_ANALOG_PLL_SYS_SS`: 528MHz System PLL Spread Spectrum Register
+    pll_sys_ss {}
+
+    /// `CCM_ANALOG_PLL_SYS_NUM`: Numerator of 528MHz System PLL Fractional Loop Divider Register
+    pll_sys_num {}
+
+    /// `CCM_ANALOG_PLL_SYS_DENOM`: Denominator of 528MHz System PLL Fractional Loop Divider Register
+    pll_sys_denom {}
+
+    /// `CCM_ANALOG_PLL_AUDIO`: Analog Audio PLL control Register
+    pll_audio {}
+
+    /// `CCM_ANALOG_PLL_AUDIO_NUM`: Numerator of Audio PLL Fractional Loop Divider Register
+    pll_audio_num {}
+
+    /// `CCM_ANALOG_PLL_AUDIO_DENOM`: Denominator of Audio PLL Fractional Loop Divider Register
+    pll_audio_denom {}
+
+    /// `CCM_ANALOG_PLL_VIDEO`: Analog Video PLL control Register
+    pll_video {}
+
+    /// `CCM_ANALOG_PLL_VIDEO_NUM`: Numerator of Video PLL Fractional Loop Divider Register
+    pll_video_num {}
+
+    /// `CCM_ANALOG_PLL_VIDEO_DENOM`: Denominator of Video PLL Fractional Loop Divider Register
+    pll_video_denom {}
+
+    /// `CCM_ANALOG_PLL_ENET`: Analog ENET PLL Control Register
+    pll_enet {}
+
+    /// `CCM_ANALOG_PFD_480`: 480MHz Clock (PLL3) Phase Fractional Divider Control Register
+    pfd_480 {}
+
+    /// `CCM_ANALOG_PFD_528`: 528MHz Clock (PLL2) Phase Fractional Divider Control Register
+    pfd_528 {
+        PFD0_FRAC: Field = 0..6,
+        PFD2_FRAC: Field = 16..22,
+    }
+
+    /// `CCM_ANALOG_MISC0`: Miscellaneous Register 0
+    misc0 {}
+
+    /// `CCM_ANALOG_MISC1`: Miscellaneous Register 1
+    misc1 {}
+
+    /// `CCM_ANALOG_MISC2`: Miscellaneous Register 2
+    misc2 {}
+}
+
+/// The field for clock gate `index` within one of the `CCGR` registers
+fn gate_field(index: usize) -> Field<ccgr::Register, ClockGate> {
+    let offset = index as u32 * 2;
+    Field::new(offset..offset + 2)
+}
+
+#[repr(C)]
 struct CcmRegs {
-    ccr: Volatile<u32>,
+    ccr: ReadWrite<ccr::Register>,
     _pad0: u32,
-    csr: ReadOnly<u32>,
-    ccsr: Volatile<u32>,
-    cacrr: Volatile<u32>,
-    cbcdr: Volatile<u32>,
-    cbcmr: Volatile<u32>,
-    cscmr: [Volatile<u32>; 2],
-    cscdr1: Volatile<u32>,
-    cs1cdr: Volatile<u32>,
-    cs2cdr: Volatile<u32>,
-    cdcdr: Volatile<u32>,
+    csr: ReadOnly<csr::Register>,
+    ccsr: ReadWrite<ccsr::Register>,
+    cacrr: ReadWrite<cacrr::Register>,
+    cbcdr: ReadWrite<cbcdr::Register>,
+    cbcmr: ReadWrite<cbcmr::Register>,
+    cscmr1: ReadWrite<cscmr1::Register>,
+    cscmr2: ReadWrite<cscmr2::Register>,
+    cscdr1: ReadWrite<cscdr1::Register>,
+    cs1cdr: ReadWrite<cs1cdr::Register>,
+    cs2cdr: ReadWrite<cs2cdr::Register>,
+    cdcdr: ReadWrite<cdcdr::Register>,
     _pad1: u32,
-    cscdr2: Volatile<u32>,
-    cscdr3: Volatile<u32>,
+    cscdr2: ReadWrite<cscdr2::Register>,
+    cscdr3: ReadWrite<cscdr3::Register>,
     _pad2: [u32; 2],
-    cdhipr: Volatile<u32>,
+    cdhipr: ReadOnly<cdhipr::Register>,
     _pad3: [u32; 2],
-    clpcr: Volatile<u32>,
-    cisr: Volatile<u32>,
-    cimr: Volatile<u32>,
-    ccosr: Volatile<u32>,
-    cgpr: Volatile<u32>,
-    ccgr: [Volatile<u32>; 8],
-    cmeor: Volatile<u32>,
-}
-
-// TODO: This should be broken out into a common way to access
-// registers like this across different hardware modules.
-struct SegmentedRegister {
-    val: Volatile<u32>,
-    set: Volatile<u32>,
-    clear: Volatile<u32>,
-    _toggle: Volatile<u32>,
+    clpcr: ReadWrite<clpcr::Register>,
+    cisr: ReadWrite<cisr::Register>,
+    cimr: ReadWrite<cimr::Register>,
+    ccosr: ReadWrite<ccosr::Register>,
+    cgpr: ReadWrite<cgpr::Register>,
+    ccgr: [ReadWrite<ccgr::Register>; 8],
+    cmeor: ReadWrite<cmeor::Register>,
 }
 
-#[repr(C, packed)]
+#[repr(C)]
 struct CcmAnalogRegs {
-    pll_arm: SegmentedRegister,
-    pll_usb1: SegmentedRegister,
-    pll_usb2: SegmentedRegister,
-    pll_sys: SegmentedRegister,
-    pll_sys_ss: Volatile<u32>,
+    pll_arm: Segmented<pll_arm::Register>,
+    pll_usb1: Segmented<pll_usb1::Register>,
+    pll_usb2: Segmented<pll_usb2::Register>,
+    pll_sys: Segmented<pll_sys::Register>,
+    pll_sys_ss: ReadWrite<pll_sys_ss::Register>,
     _pad0: [u32; 3],
-    pll_sys_num: Volatile<u32>,
+    pll_sys_num: ReadWrite<pll_sys_num::Register>,
     _pad1: [u32; 3],
-    pll_sys_denom: Volatile<u32>,
+    pll_sys_denom: ReadWrite<pll_sys_denom::Register>,
     _pad2: [u32; 3],
-    pll_audio: SegmentedRegister,
-    pll_audio_num: Volatile<u32>,
+    pll_audio: Segmented<pll_audio::Register>,
+    pll_audio_num: ReadWrite<pll_audio_num::Register>,
     _pad3: [u32; 3],
-    pll_audio_denom: Volatile<u32>,
+    pll_audio_denom: ReadWrite<pll_audio_denom::Register>,
     _pad4: [u32; 3],
-    pll_video: SegmentedRegister,
-    pll_video_num: Volatile<u32>,
+    pll_video: Segmented<pll_video::Register>,
+    pll_video_num: ReadWrite<pll_video_num::Register>,
     _pad5: [u32; 3],
-    pll_video_denom: Volatile<u32>,
+    pll_video_denom: ReadWrite<pll_video_denom::Register>,
     _pad6: [u32; 7],
-    pll_enet: SegmentedRegister,
-    pfd_480: SegmentedRegister,
-    pfd_528: SegmentedRegister,
+    pll_enet: Segmented<pll_enet::Register>,
+    pfd_480: Segmented<pfd_480::Register>,
+    pfd_528: Segmented<pfd_528::Register>,
     _pad7: [u32; 16],
-    misc0: SegmentedRegister,
-    misc1: SegmentedRegister,
-    misc2: SegmentedRegister,
+    misc0: Segmented<misc0::Register>,
+    misc1: Segmented<misc1::Register>,
+    misc2: Segmented<misc2::Register>,
 }
 
 /// The ARM PLL (PLL1)
@@ -425,14 +585,10 @@ where
 {
     /// Disables this PLL to conserve power
     pub fn disable(&mut self) {
-        unsafe {
-            // [pll_arm[bypass]
-            self.ccm.analog.pll_arm.set.write(1 << 16);
-            // pll_arm[enable]
-            self.ccm.analog.pll_arm.clear.write(1 << 13);
-            // pll_arm[powerdown]
-            self.ccm.analog.pll_arm.set.write(1 << 12);
-        }
+        let pll = &mut self.ccm.analog.pll_arm;
+        pll.write_set(pll_arm::BYPASS);
+        pll.write_clear(pll_arm::ENABLE);
+        pll.write_set(pll_arm::POWERDOWN);
     }
 }
 
@@ -441,18 +597,12 @@ where
     CCM: Deref<Target = Ccm>,
 {
     pub fn multiplier(&self) -> PeripheralPllMultiplier {
-        unsafe {
-            // pll_usb1[[div_select]
-            self.ccm.analog.pll_usb1.val.read().get_bits(1..2).into()
-        }
+        self.ccm.analog.pll_usb1.get(pll_usb1::DIV_SELECT)
     }
 
     pub fn enabled(&self) -> bool {
-        unsafe {
-            // pll_usb1[power] && pll_usb1[enable]
-            self.ccm.analog.pll_usb1.val.read().get_bit(12)
-                && self.ccm.analog.pll_usb1.val.read().get_bit(13)
-        }
+        let pll = self.ccm.analog.pll_usb1.read();
+        pll.is_set(pll_usb1::POWER) && pll.is_set(pll_usb1::ENABLE)
     }
 }
 
@@ -462,8 +612,7 @@ where
 {
     /// Query the current clock source used by this mux
     pub fn input(&self) -> PeriphClockInput {
-        // cbcdr[periph_clk_sel]
-        unsafe { self.ccm.regs.cbcdr.read().get_bits(25..26).into() }
+        self.ccm.regs.cbcdr.get(cbcdr::PERIPH_CLK_SEL)
     }
 }
 
@@ -474,18 +623,11 @@ where
     /// Set the clock source used for this mux.
     pub fn set_input(&mut self, input: PeriphClockInput) {
         // TODO: Verify clock speeds for downstream blocks.
-        unsafe {
-            self.ccm.regs.cbcdr.update(|r| {
-                // cbcdr[periph_clk_sel]
-                r.set_bits(25..26, input.into());
-            });
-
-            // Once we've set the clock input, we need to wait for the
-            // transfer to complete.
+        self.ccm.regs.cbcdr.set(cbcdr::PERIPH_CLK_SEL, input);
 
-            // cdhipr[periph_clk_sel_busy]
-            while self.ccm.regs.cdhipr.read().get_bit(5) {}
-        }
+        // Once we've set the clock input, we need to wait for the
+        // transfer to complete.
+        while self.ccm.regs.cdhipr.is_set(cdhipr::PERIPH_CLK_SEL_BUSY) {}
     }
 }
 
@@ -495,8 +637,7 @@ where
 {
     /// Query the current clock source used by this mux
     pub fn input(&self) -> PeriphClock2Input {
-        // cbcmr[periph_clk2_sel]
-        unsafe { self.ccm.regs.cbcmr.read().get_bits(12..14).into() }
+        self.ccm.regs.cbcmr.get(cbcmr::PERIPH_CLK2_SEL)
     }
 }
 
@@ -506,18 +647,11 @@ where
 {
     /// Set the clock source used for this mux.
     pub fn set_input(&mut self, input: PeriphClock2Input) {
-        unsafe {
-            self.ccm.regs.cbcmr.update(|r| {
-                // cbcmr[periph_clk2_sel]
-                r.set_bits(12..14, input.into());
-            });
-
-            // Once we've set the clock input, we need to wait for the
-            // transfer to complete.
+        self.ccm.regs.cbcmr.set(cbcmr::PERIPH_CLK2_SEL, input);
 
-            // cdhipr[periph2_clk_sel_busy]
-            while self.ccm.regs.cdhipr.read().get_bit(3) {}
-        }
+        // Once we've set the clock input, we need to wait for the
+        // transfer to complete.
+        while self.ccm.regs.cdhipr.is_set(cdhipr::PERIPH2_CLK_SEL_BUSY) {}
     }
 }
 
@@ -527,8 +661,7 @@ where
 {
     /// Query the current clock source used by this mux
     pub fn input(&self) -> PrePeriphClockInput {
-        // cbcmr[pre_periph_clk_sel]
-        unsafe { self.ccm.regs.cbcmr.read().get_bits(18..20).into() }
+        self.ccm.regs.cbcmr.get(cbcmr::PRE_PERIPH_CLK_SEL)
     }
 }
 
@@ -538,12 +671,7 @@ where
 {
     /// Set the clock source used by this mux
     pub fn set_input(&mut self, input: PrePeriphClockInput) {
-        unsafe {
-            self.ccm.regs.cbcmr.update(|r| {
-                // cbcmr[pre_periph_clk_sel]
-                r.set_bits(18..20, input.into());
-            });
-        }
+        self.ccm.regs.cbcmr.set(cbcmr::PRE_PERIPH_CLK_SEL, input);
     }
 }
 
@@ -553,16 +681,12 @@ where
 {
     /// Query the current clock source used by this mux
     pub fn input(&self) -> UartClockInput {
-        // cscdr1[uart_clk_sel]
-        unsafe { self.ccm.regs.cscdr1.read().get_bits(6..7).into() }
+        self.ccm.regs.cscdr1.get(cscdr1::UART_CLK_SEL)
     }
 
     /// Query the current post-divider for the UART clocks
     pub fn divisor(&self) -> u32 {
-        unsafe {
-            // cscdr1[uart_clk_podf]
-            self.ccm.regs.cscdr1.read().get_bits(0..6) + 1
-        }
+        self.ccm.regs.cscdr1.get(cscdr1::UART_CLK_PODF) + 1
     }
 }
 
@@ -572,30 +696,108 @@ where
 {
     /// Set the clock source used by this mux
     pub fn set_input(&mut self, input: UartClockInput) {
-        unsafe {
-            self.ccm.regs.cscdr1.update(|r| {
-                // cscdr1[uart_clk_sel]
-                r.set_bits(6..7, input.into());
-            });
-        }
+        self.ccm.regs.cscdr1.set(cscdr1::UART_CLK_SEL, input);
     }
 
     /// Set the divisor for the clock used by this mux
     pub fn set_divisor(&mut self, divisor: u32) {
-        unsafe {
-            self.ccm.regs.cscdr1.update(|r| {
-                // cscdr1[uart_clk_podf]
-                r.set_bits(0..6, divisor - 1);
-            });
+        self.ccm.regs.cscdr1.set(cscdr1::UART_CLK_PODF, divisor - 1);
+    }
+}
+
+/// The frequency of the 24MHz oscillator
+const OSCILLATOR_HZ: u32 = 24_000_000;
+
+impl PeripheralPllMultiplier {
+    /// The factor applied to the oscillator
+    fn factor(self) -> u32 {
+        match self {
+            PeripheralPllMultiplier::Twenty => 20,
+            PeripheralPllMultiplier::TwentyTwo => 22,
         }
     }
 }
 
-static CCM_INIT: AtomicBool = AtomicBool::new(false);
+/// Work out the ARM core clock frequency, in Hz, from the PLLs, muxes
+/// and dividers which feed it
+fn core_clock_frequency(regs: &CcmRegs, analog: &CcmAnalogRegs) -> u32 {
+    let system_pll = || {
+        let pll = analog.pll_sys.read();
+        if pll.is_set(pll_sys::BYPASS) {
+            OSCILLATOR_HZ
+        } else {
+            OSCILLATOR_HZ * pll.get(pll_sys::DIV_SELECT).factor()
+        }
+    };
+    // The PFDs multiply their input by 18 / FRAC
+    let system_pll_pfd = |frac| (system_pll() as u64 * 18 / frac as u64) as u32;
+
+    let cbcdr = regs.cbcdr.read();
+    let periph_clock = match cbcdr.get(cbcdr::PERIPH_CLK_SEL) {
+        PeriphClockInput::PrePeriphClock => match regs.cbcmr.get(cbcmr::PRE_PERIPH_CLK_SEL) {
+            PrePeriphClockInput::SystemPll => system_pll(),
+            PrePeriphClockInput::SystemPllPfd0 => {
+                system_pll_pfd(analog.pfd_528.get(pfd_528::PFD0_FRAC))
+            }
+            PrePeriphClockInput::SystemPllPfd2 => {
+                system_pll_pfd(analog.pfd_528.get(pfd_528::PFD2_FRAC))
+            }
+            PrePeriphClockInput::ArmPll => {
+                let pll = analog.pll_arm.read();
+                let pll_hz = if pll.is_set(pll_arm::BYPASS) {
+                    OSCILLATOR_HZ
+                } else {
+                    OSCILLATOR_HZ / 2 * pll.get(pll_arm::DIV_SELECT)
+                };
+                pll_hz / (regs.cacrr.get(cacrr::ARM_PODF) + 1)
+            }
+        },
+        PeriphClockInput::PeriphClock2 => {
+            let input = match regs.cbcmr.get(cbcmr::PERIPH_CLK2_SEL) {
+                PeriphClock2Input::Usb1Pll => {
+                    let pll = analog.pll_usb1.read();
+                    if pll.is_set(pll_usb1::BYPASS) {
+                        OSCILLATOR_HZ
+                    } else {
+                        OSCILLATOR_HZ * pll.get(pll_usb1::DIV_SELECT).factor()
+                    }
+                }
+                PeriphClock2Input::Oscillator | PeriphClock2Input::SystemPllBypass => OSCILLATOR_HZ,
+            };
+            input / (cbcdr.get(cbcdr::PERIPH_CLK2_PODF) + 1)
+        }
+    };
+    periph_clock / (cbcdr.get(cbcdr::AHB_PODF) + 1)
+}
+
+/// Read the ARM core clock frequency, in Hz, without a [`Ccm`]
+///
+/// # Safety
+/// The clock configuration must not change while this runs. This is
+/// meant for panic handlers, which can't get at the program's `Ccm`.
+#[cfg(feature = "panic-led")]
+pub(crate) unsafe fn steal_core_clock_frequency() -> u32 {
+    let regs = &*(0x400F_C000 as *const CcmRegs);
+    let analog = &*(0x400D_8000 as *const CcmAnalogRegs);
+    core_clock_frequency(regs, analog)
+}
+
+/// Turn on the clock gate of `T` without a [`Ccm`]
+///
+/// # Safety
+/// See [`Ccm::set_clock_gate`]. This is meant for code which takes
+/// hardware by force, and can't get at the program's `Ccm`.
+pub(crate) unsafe fn steal_clock_gate<T: ClockGated>() {
+    let regs = &mut *(0x400F_C000 as *mut CcmRegs);
+    let (reg, gate) = T::GATE;
+    regs.ccgr[reg].set(gate_field(gate), ClockGate::Enabled);
+}
+
+static CCM_INIT: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
 
 impl Drop for Ccm {
     fn drop(&mut self) {
-        CCM_INIT.swap(false, Ordering::Release);
+        critical_section::with(|cs| CCM_INIT.borrow(cs).set(false));
     }
 }
 
@@ -606,7 +808,7 @@ impl Ccm {
     /// This will panic if there is an outstanding reference to the
     /// CCM.
     pub fn new() -> Ccm {
-        let was_init = CCM_INIT.swap(true, Ordering::Acquire);
+        let was_init = critical_section::with(|cs| CCM_INIT.borrow(cs).replace(true));
         if was_init {
             panic!("Cannot initialize CCM: An instance is already outstanding");
         }
@@ -615,6 +817,21 @@ impl Ccm {
         Ccm { regs, analog }
     }
 
+    /// Grab the CCM, even if an instance is already outstanding
+    ///
+    /// The returned instance must never be dropped, as that would
+    /// release the claim held by the real owner.
+    ///
+    /// # Safety
+    /// This aliases any existing [`Ccm`]. It must only be called once
+    /// nothing else will run, such as in a panic handler.
+    #[cfg(feature = "panic-uart")]
+    pub(crate) unsafe fn steal() -> Ccm {
+        let regs = &mut *(0x400F_C000 as *mut CcmRegs);
+        let analog = &mut *(0x400D_8000 as *mut CcmAnalogRegs);
+        Ccm { regs, analog }
+    }
+
     /// Enable a [`ClockGated`] hardware module.
     ///
     /// This will force the peripheral to be always on, even when the
@@ -738,10 +955,25 @@ impl Ccm {
         }
     }
 
+    /// Get the [`UART_CLK_SEL` mux](UartClockSelector) mutably, even
+    /// if UART clock gates are enabled
+    ///
+    /// # Safety
+    /// Any UART which is clocked while the mux changes may glitch or
+    /// end up at the wrong baud rate.
+    #[cfg(feature = "panic-uart")]
+    pub(crate) unsafe fn uart_clock_selector_unchecked(&mut self) -> UartClockSelector<&mut Self> {
+        UartClockSelector { ccm: self }
+    }
+
+    /// The frequency of the ARM core clock, `AHB_CLK_ROOT`, in Hz
+    pub fn core_clock_frequency(&self) -> u32 {
+        core_clock_frequency(self.regs, self.analog)
+    }
+
     /// Query the status of a clock gate
     pub fn clock_gate(&self, gate: (usize, usize)) -> ClockGate {
-        let gate_bits = (gate.1 * 2)..(gate.1 * 2 + 2);
-        unsafe { self.regs.ccgr[gate.0].read().get_bits(gate_bits).into() }
+        self.regs.ccgr[gate.0].get(gate_field(gate.1))
     }
 
     /// Toggle the status of a clock gate
@@ -751,10 +983,7 @@ impl Ccm {
     /// * The clock gate for a device must only be enabled if the
     ///   clock path leading to the gate is safe for the device.
     pub unsafe fn set_clock_gate(&mut self, gate: (usize, usize), state: ClockGate) {
-        let gate_bits = (gate.1 * 2)..(gate.1 * 2 + 2);
-        self.regs.ccgr[gate.0].update(|r| {
-            r.set_bits(gate_bits, state.into());
-        });
+        self.regs.ccgr[gate.0].set(gate_field(gate.1), state);
     }
 
     /// Sanitize the clocking environment to bring us to the safest, simplest configuration
@@ -1,99 +1,115 @@
 //! Debugging helpers using GPIOs
 //!
-//! These functions allow turning on various pins of the Teensy in
-//! order to provide status information when other debugging methods
-//! are unavailable. They claim various hardware units without regards
-//! to what other code has done, and as such are all unsafe.
-
-use core::sync::atomic::{AtomicU8, Ordering};
-
-/// Enable the GPIO for debug output
-///
-/// # Safety
-/// * This function must be called before any other debug function
-/// * GPIO1, GPIO2, GPIO6, and GPIO7 cannot be used once this has been
-/// called.
-pub unsafe fn enable() {
-    // Switch from GPIO1 to GPIO 6
-    let reg = 0x400A_C068 as *mut u32;
-    core::ptr::write_volatile(reg, 0xFFFF_FFFF);
-
-    // Switch from GPIO2 to GPIO 7
-    let reg = 0x400A_C06C as *mut u32;
-    core::ptr::write_volatile(reg, 0xFFFF_FFFF);
-
-    // Set GPIO6 to output mode
-    let reg = 0x4200_0004 as *mut u32;
-    core::ptr::write_volatile(reg, 0xFFFF_FFFF);
-
-    // Set GPIO7 to output mode
-    let reg = 0x4200_4004 as *mut u32;
-    core::ptr::write_volatile(reg, 0xFFFF_FFFF);
-}
-
-unsafe fn pin(pin: u32, reg: *mut u32) {
-    core::ptr::write_volatile(reg, 1 << pin);
+//! These provide status information through the Teensy's pins when
+//! other debugging methods are unavailable. Each helper owns the pins
+//! it drives, so it never disturbs the rest of the board:
+//!
+//! ```ignore
+//! let mut led = DebugPin::new(pins.led, iomuxc.gpr_mut());
+//! led.on();
+//!
+//! let bar = [pins.p14.into_push_pull_output(gpr).erase(), /* ... */];
+//! let mut progress = Progress::new(bar);
+//! progress.advance();
+//! ```
+//!
+//! A panic handler can't get at pins owned by the rest of the program,
+//! so [`steal_led`] takes the LED regardless of who owns it.
+
+use super::{
+    board::Led,
+    gpio::{AnyPin, GpioPad, IntoGpio, Output, PushPull},
+    iomuxc::gpr::Gpr,
+};
+
+/// A single pin used as a status indicator
+pub struct DebugPin {
+    pin: AnyPin<Output<PushPull>>,
 }
 
-/// Turn on the Teensy's orange LED.
-pub unsafe fn led() {
-    pin(3, 0x4200_4084 as *mut u32);
-}
+impl DebugPin {
+    /// Take over a pad as a status indicator, initially off
+    pub fn new<P: GpioPad>(pad: P, gpr: &mut Gpr) -> DebugPin {
+        DebugPin::from(pad.into_push_pull_output(gpr).erase())
+    }
 
-/// Turn on the Teensy's pin 6
-pub unsafe fn pin06() {
-    pin(10, 0x4200_4084 as *mut u32);
-}
+    /// Turn the indicator on
+    pub fn on(&mut self) {
+        self.pin.set_high();
+    }
 
-/// Turn on the Teensy's pin 7
-pub unsafe fn pin07() {
-    pin(17, 0x4200_4084 as *mut u32);
-}
+    /// Turn the indicator off
+    pub fn off(&mut self) {
+        self.pin.set_low();
+    }
 
-/// Turn on the Teensy's pin 8
-pub unsafe fn pin08() {
-    pin(16, 0x4200_4084 as *mut u32);
-}
+    /// Flip the indicator between on and off
+    pub fn toggle(&mut self) {
+        self.pin.toggle();
+    }
 
-/// Turn on the Teensy's pin 9
-pub unsafe fn pin09() {
-    pin(11, 0x4200_4084 as *mut u32);
+    /// Give back the underlying pin
+    pub fn release(self) -> AnyPin<Output<PushPull>> {
+        self.pin
+    }
 }
 
-/// Turn on the Teensy's pin 10
-pub unsafe fn pin10() {
-    pin(0, 0x4200_4084 as *mut u32);
+impl From<AnyPin<Output<PushPull>>> for DebugPin {
+    fn from(pin: AnyPin<Output<PushPull>>) -> DebugPin {
+        DebugPin { pin }
+    }
 }
 
-/// Turn on the Teensy's pin 11
-pub unsafe fn pin11() {
-    pin(2, 0x4200_4084 as *mut u32);
+/// Take the Teensy's orange LED for a panic handler
+///
+/// This routes the LED's pad to GPIO and makes it an output, touching
+/// none of the other pins in its bank. The IOMUXC clock is turned on
+/// first, since startup leaves it off until the program enables the
+/// [`Iomuxc`](super::iomuxc::Iomuxc).
+///
+/// # Safety
+/// This ignores any existing owner of the LED pin, and any
+/// [`Gpr`] handle. It must only be called once nothing else will run,
+/// such as in a panic handler.
+pub unsafe fn steal_led() -> DebugPin {
+    DebugPin::new(Led::steal(), &mut Gpr::new())
 }
 
-/// Turn on the Teensy's pin 12
-pub unsafe fn pin12() {
-    pin(1, 0x4200_4084 as *mut u32);
+/// A bar graph of LEDs which lights up one more segment at each step
+///
+/// Segments light in the order of the pins given to [`Progress::new`].
+pub struct Progress<const N: usize> {
+    pins: [AnyPin<Output<PushPull>>; N],
+    lit: usize,
 }
 
-static PROGRESS_COUNTER: AtomicU8 = AtomicU8::new(0);
-const PROGRESS_MAX: u8 = 10;
+impl<const N: usize> Progress<N> {
+    /// Make a bar graph from `pins`, with every segment off
+    pub fn new(mut pins: [AnyPin<Output<PushPull>>; N]) -> Progress<N> {
+        for pin in pins.iter_mut() {
+            pin.set_low();
+        }
+        Progress { pins, lit: 0 }
+    }
 
-/// Increment the progress bar
-///
-/// This function increments a 10-segment LED bar graph connected to
-/// the Teensy's pins 14 through 23.
-///
-/// # Safety
-/// Must call [`enable`] first
-pub unsafe fn progress() {
-    if PROGRESS_COUNTER.load(Ordering::Relaxed) >= PROGRESS_MAX {
-        return;
+    /// Light the next segment, if any are left
+    pub fn advance(&mut self) {
+        if let Some(pin) = self.pins.get_mut(self.lit) {
+            pin.set_high();
+            self.lit += 1;
+        }
     }
-    let idx = PROGRESS_COUNTER.fetch_add(1, Ordering::Relaxed);
-    if idx >= PROGRESS_MAX {
-        return;
+
+    /// Turn every segment off
+    pub fn reset(&mut self) {
+        for pin in self.pins.iter_mut() {
+            pin.set_low();
+        }
+        self.lit = 0;
     }
-    let shift = [18, 19, 23, 22, 17, 16, 26, 27, 24, 25][idx as usize];
 
-    pin(shift, 0x4200_0084 as *mut u32);
+    /// Give back the pins
+    pub fn release(self) -> [AnyPin<Output<PushPull>>; N] {
+        self.pins
+    }
 }
@@ -0,0 +1,352 @@
+//! Fault handlers
+//!
+//! The `HardFault`, `MemoryManagement`, `BusFault` and `UsageFault`
+//! exceptions all go to a handler which gathers up what the core
+//! knows about the fault into a [`FaultInfo`]. That is then passed to
+//! `FaultHook`, which the program can define:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub fn FaultHook(info: &teensy40::fault::FaultInfo) -> ! {
+//!     // ... log info somewhere it will survive a reset ...
+//!     loop {}
+//! }
+//! ```
+//!
+//! As with interrupt handlers, the signature isn't checked, so take
+//! care to match it. Without a hook of its own, the program gets one
+//! which prints the fault through the [`panic_uart`](super::panic_uart)
+//! if the `panic-uart` feature is enabled, and then spins forever.
+//!
+//! The handler moves onto a small stack of its own before doing
+//! anything else, so the hook still runs after the main stack has
+//! overflowed. It has [`FAULT_STACK_SIZE`] bytes to work with.
+//!
+//! A program can still replace the handler for any one of the faults
+//! by defining it, as described in [`interrupt`](super::interrupt).
+
+use super::register::{Bit, Value};
+use core::{
+    arch::{asm, global_asm},
+    fmt,
+    ptr::{read_volatile, write_volatile},
+};
+
+register! {
+    /// `CFSR`: Configurable Fault Status Register
+    cfsr {
+        IACCVIOL: Bit = 0,
+        DACCVIOL: Bit = 1,
+        MUNSTKERR: Bit = 3,
+        MSTKERR: Bit = 4,
+        MLSPERR: Bit = 5,
+        MMARVALID: Bit = 7,
+        IBUSERR: Bit = 8,
+        PRECISERR: Bit = 9,
+        IMPRECISERR: Bit = 10,
+        UNSTKERR: Bit = 11,
+        STKERR: Bit = 12,
+        LSPERR: Bit = 13,
+        BFARVALID: Bit = 15,
+        UNDEFINSTR: Bit = 16,
+        INVSTATE: Bit = 17,
+        INVPC: Bit = 18,
+        NOCP: Bit = 19,
+        UNALIGNED: Bit = 24,
+        DIVBYZERO: Bit = 25,
+    }
+
+    /// `HFSR`: HardFault Status Register
+    hfsr {
+        VECTTBL: Bit = 1,
+        FORCED: Bit = 30,
+        DEBUGEVT: Bit = 31,
+    }
+}
+
+/// `SHCSR`: System Handler Control and State Register
+const SHCSR: *mut u32 = 0xE000_ED24 as *mut u32;
+/// `CFSR`: Configurable Fault Status Register
+const CFSR: *const u32 = 0xE000_ED28 as *const u32;
+/// `HFSR`: HardFault Status Register
+const HFSR: *const u32 = 0xE000_ED2C as *const u32;
+/// `MMFAR`: MemManage Fault Address Register
+const MMFAR: *const u32 = 0xE000_ED34 as *const u32;
+/// `BFAR`: BusFault Address Register
+const BFAR: *const u32 = 0xE000_ED38 as *const u32;
+
+/// `SHCSR` bits enabling the `UsageFault`, `BusFault` and
+/// `MemoryManagement` exceptions
+const SHCSR_FAULTS: u32 = 1 << 18 | 1 << 17 | 1 << 16;
+
+/// Give each fault its own exception
+///
+/// Otherwise they all escalate to `HardFault`, leaving only `FORCED`
+/// set in its status.
+pub(crate) unsafe fn enable() {
+    write_volatile(SHCSR, read_volatile(SHCSR) | SHCSR_FAULTS);
+    asm!("dsb", "isb");
+}
+
+/// The registers the core pushed onto the stack on taking the fault
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct ExceptionFrame {
+    pub r0: u32,
+    pub r1: u32,
+    pub r2: u32,
+    pub r3: u32,
+    pub r12: u32,
+    pub lr: u32,
+    /// The address of the faulting instruction, or the next one for
+    /// an imprecise bus fault
+    pub pc: u32,
+    pub xpsr: u32,
+}
+
+/// Which exception was taken
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Fault {
+    HardFault,
+    MemoryManagement,
+    BusFault,
+    UsageFault,
+}
+
+/// A reason for a fault, from `CFSR` or `HFSR`
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Cause {
+    InstructionAccessViolation,
+    DataAccessViolation,
+    MemManageUnstacking,
+    MemManageStacking,
+    MemManageLazyFpState,
+    InstructionBusError,
+    PreciseDataBusError,
+    ImpreciseDataBusError,
+    BusUnstacking,
+    BusStacking,
+    BusLazyFpState,
+    UndefinedInstruction,
+    InvalidState,
+    InvalidPcLoad,
+    NoCoprocessor,
+    UnalignedAccess,
+    DivideByZero,
+    VectorTableRead,
+    Forced,
+    DebugEvent,
+}
+
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Cause::InstructionAccessViolation => "instruction fetch from protected memory",
+            Cause::DataAccessViolation => "data access to protected memory",
+            Cause::MemManageUnstacking => "MPU fault unstacking from an exception",
+            Cause::MemManageStacking => "MPU fault stacking for an exception",
+            Cause::MemManageLazyFpState => "MPU fault saving the FPU state",
+            Cause::InstructionBusError => "bus error on an instruction fetch",
+            Cause::PreciseDataBusError => "bus error on a data access",
+            Cause::ImpreciseDataBusError => "bus error on an earlier data write",
+            Cause::BusUnstacking => "bus error unstacking from an exception",
+            Cause::BusStacking => "bus error stacking for an exception",
+            Cause::BusLazyFpState => "bus error saving the FPU state",
+            Cause::UndefinedInstruction => "undefined instruction",
+            Cause::InvalidState => "invalid execution state",
+            Cause::InvalidPcLoad => "invalid exception return",
+            Cause::NoCoprocessor => "coprocessor disabled or missing",
+            Cause::UnalignedAccess => "unaligned access",
+            Cause::DivideByZero => "divide by zero",
+            Cause::VectorTableRead => "bus error reading the vector table",
+            Cause::Forced => "escalated from another fault",
+            Cause::DebugEvent => "debug event",
+        })
+    }
+}
+
+const CFSR_CAUSES: [(Bit<cfsr::Register>, Cause); 17] = [
+    (cfsr::IACCVIOL, Cause::InstructionAccessViolation),
+    (cfsr::DACCVIOL, Cause::DataAccessViolation),
+    (cfsr::MUNSTKERR, Cause::MemManageUnstacking),
+    (cfsr::MSTKERR, Cause::MemManageStacking),
+    (cfsr::MLSPERR, Cause::MemManageLazyFpState),
+    (cfsr::IBUSERR, Cause::InstructionBusError),
+    (cfsr::PRECISERR, Cause::PreciseDataBusError),
+    (cfsr::IMPRECISERR, Cause::ImpreciseDataBusError),
+    (cfsr::UNSTKERR, Cause::BusUnstacking),
+    (cfsr::STKERR, Cause::BusStacking),
+    (cfsr::LSPERR, Cause::BusLazyFpState),
+    (cfsr::UNDEFINSTR, Cause::UndefinedInstruction),
+    (cfsr::INVSTATE, Cause::InvalidState),
+    (cfsr::INVPC, Cause::InvalidPcLoad),
+    (cfsr::NOCP, Cause::NoCoprocessor),
+    (cfsr::UNALIGNED, Cause::UnalignedAccess),
+    (cfsr::DIVBYZERO, Cause::DivideByZero),
+];
+
+const HFSR_CAUSES: [(Bit<hfsr::Register>, Cause); 3] = [
+    (hfsr::VECTTBL, Cause::VectorTableRead),
+    (hfsr::FORCED, Cause::Forced),
+    (hfsr::DEBUGEVT, Cause::DebugEvent),
+];
+
+/// Everything the core records about a fault
+#[derive(Copy, Clone, Debug)]
+pub struct FaultInfo {
+    /// The exception taken
+    pub fault: Fault,
+    /// The registers at the time of the fault
+    ///
+    /// This is `None` if the core failed to save them, which is what
+    /// happens when the stack overflows.
+    pub frame: Option<ExceptionFrame>,
+    /// The raw `CFSR`
+    pub cfsr: u32,
+    /// The raw `HFSR`
+    pub hfsr: u32,
+    /// The address of a memory management fault, if known
+    pub mmfar: Option<u32>,
+    /// The address of a bus fault, if known
+    pub bfar: Option<u32>,
+}
+
+impl FaultInfo {
+    /// The reasons recorded in `CFSR` and `HFSR`
+    ///
+    /// Several may be given at once, including ones left over from
+    /// faults which were handled by escalating to this one.
+    pub fn causes(&self) -> impl Iterator<Item = Cause> {
+        let cfsr = Value::<cfsr::Register>::from_bits(self.cfsr);
+        let hfsr = Value::<hfsr::Register>::from_bits(self.hfsr);
+        let cfsr_causes = CFSR_CAUSES
+            .iter()
+            .filter(move |&&(bit, _)| cfsr.is_set(bit))
+            .map(|&(_, cause)| cause);
+        let hfsr_causes = HFSR_CAUSES
+            .iter()
+            .filter(move |&&(bit, _)| hfsr.is_set(bit))
+            .map(|&(_, cause)| cause);
+        cfsr_causes.chain(hfsr_causes)
+    }
+}
+
+impl fmt::Display for FaultInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.frame {
+            Some(frame) => writeln!(f, "{:?} at PC {:#010X}", self.fault, frame.pc)?,
+            None => writeln!(f, "{:?}", self.fault)?,
+        }
+        for cause in self.causes() {
+            writeln!(f, "  {}", cause)?;
+        }
+        if let Some(address) = self.mmfar {
+            writeln!(f, "  MMFAR {:#010X}", address)?;
+        }
+        if let Some(address) = self.bfar {
+            writeln!(f, "  BFAR  {:#010X}", address)?;
+        }
+        let frame = match &self.frame {
+            Some(frame) => frame,
+            None => return write!(f, "  No registers were saved"),
+        };
+        writeln!(
+            f,
+            "  R0  {:#010X}  R1 {:#010X}  R2 {:#010X}  R3   {:#010X}",
+            frame.r0, frame.r1, frame.r2, frame.r3
+        )?;
+        write!(
+            f,
+            "  R12 {:#010X}  LR {:#010X}  PC {:#010X}  xPSR {:#010X}",
+            frame.r12, frame.lr, frame.pc, frame.xpsr
+        )
+    }
+}
+
+/// The size of the stack the fault handler and hook run on, in bytes
+pub const FAULT_STACK_SIZE: usize = 4096;
+
+#[repr(C, align(8))]
+struct FaultStack([u8; FAULT_STACK_SIZE]);
+
+static mut FAULT_STACK: FaultStack = FaultStack([0; FAULT_STACK_SIZE]);
+
+// The entry point for all four faults. Bit 2 of the EXC_RETURN value
+// in LR says which stack the frame was pushed to. The stack pointer is
+// then moved to the fault stack without pushing anything, as the main
+// stack may be the reason we're here. We never return, so there's no
+// need to put it back.
+global_asm!(
+    ".section .text.FaultHandler_, \"ax\", %progbits",
+    ".global FaultHandler_",
+    ".type FaultHandler_, %function",
+    ".thumb_func",
+    "FaultHandler_:",
+    "tst lr, #4",
+    "ite eq",
+    "mrseq r0, msp",
+    "mrsne r0, psp",
+    "movw r1, :lower16:{stack}",
+    "movt r1, :upper16:{stack}",
+    "add.w r1, r1, #{size}",
+    "mov sp, r1",
+    "b {handler}",
+    stack = sym FAULT_STACK,
+    size = const FAULT_STACK_SIZE,
+    handler = sym handle_fault,
+);
+
+extern "Rust" {
+    fn FaultHook(info: &FaultInfo) -> !;
+}
+
+unsafe extern "C" fn handle_fault(frame: *const ExceptionFrame) -> ! {
+    let ipsr: u32;
+    asm!("mrs {}, IPSR", out(reg) ipsr, options(nomem, nostack, preserves_flags));
+    let fault = match ipsr & 0x1FF {
+        4 => Fault::MemoryManagement,
+        5 => Fault::BusFault,
+        6 => Fault::UsageFault,
+        _ => Fault::HardFault,
+    };
+
+    // A failure to stack leaves the frame pointing at memory which
+    // can't be trusted, or even read
+    let cfsr = Value::<cfsr::Register>::from_bits(read_volatile(CFSR));
+    let stacked = !cfsr.is_set(cfsr::MSTKERR) && !cfsr.is_set(cfsr::STKERR);
+    let info = FaultInfo {
+        fault,
+        frame: if stacked { Some(*frame) } else { None },
+        cfsr: cfsr.bits(),
+        hfsr: read_volatile(HFSR),
+        mmfar: if cfsr.is_set(cfsr::MMARVALID) {
+            Some(read_volatile(MMFAR))
+        } else {
+            None
+        },
+        bfar: if cfsr.is_set(cfsr::BFARVALID) {
+            Some(read_volatile(BFAR))
+        } else {
+            None
+        },
+    };
+    FaultHook(&info)
+}
+
+/// The hook for any fault, unless the program defines `FaultHook`
+///
+/// The linker script points `FaultHook` here.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub fn DefaultFaultHook_(info: &FaultInfo) -> ! {
+    #[cfg(feature = "panic-uart")]
+    unsafe {
+        super::panic_uart::print(format_args!("\r\n{}\r\n", info));
+    }
+    #[cfg(not(feature = "panic-uart"))]
+    let _ = info;
+
+    loop {
+        core::hint::spin_loop();
+    }
+}
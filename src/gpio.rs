@@ -0,0 +1,787 @@
+//! General Purpose I/O
+//!
+//! Any pad from [`iomuxc::pin`](../iomuxc/pin/index.html) can be
+//! turned into a GPIO with the methods of [`IntoGpio`]. The resulting
+//! [`GpioPin`] tracks whether it is an input or an output in its type:
+//!
+//! ```ignore
+//! let mut led = pins.led.into_push_pull_output(iomuxc.gpr_mut());
+//! led.set_high();
+//!
+//! let button = pins.p2.into_pull_up_input(iomuxc.gpr_mut());
+//! if button.is_low() {
+//!     led.set_low();
+//! }
+//! ```
+//!
+//! Pins also implement the `embedded_hal::digital` traits, so they can
+//! be handed to drivers written against `embedded-hal`. Pins of
+//! different pads can be kept together, for example in an array, once
+//! they are [erased](GpioPin::erase) into an [`AnyPin`].
+//!
+//! Several erased pins from the same bank can be gathered into a
+//! [`PortGroup`], which writes or reads all of them at once:
+//!
+//! ```ignore
+//! let mut bus = PortGroup::new([d0.erase(), d1.erase(), d2.erase()]).unwrap();
+//! bus.write(0b101);
+//! ```
+//!
+//! Each pad's GPIO function belongs to one of the standard-speed banks
+//! `GPIO1` through `GPIO4`. Those sit on the slow IPG bus, so pins
+//! converted here are always routed to the matching fast bank `GPIO6`
+//! through `GPIO9` instead, through the [`Gpr`].
+//!
+//! # Interrupts
+//!
+//! Inputs can raise an interrupt on an edge or a level, chosen with
+//! [`GpioPin::enable_interrupt`]. The fast banks share a single
+//! interrupt line, `GPIO6_7_8_9`, whose [handler](../interrupt/index.html)
+//! should call [`dispatch_interrupts`]. That calls the handler
+//! registered for each pin with a pending interrupt, then clears the
+//! interrupt:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub unsafe extern "C" fn GPIO6_7_8_9() {
+//!     gpio::dispatch_interrupts();
+//! }
+//!
+//! fn pressed() {
+//!     // ...
+//! }
+//!
+//! let mut button = pins.p2.into_pull_up_input(iomuxc.gpr_mut());
+//! button.set_interrupt_handler(Some(pressed));
+//! button.enable_interrupt(Edge::Falling);
+//! ```
+
+use super::iomuxc::{
+    gpr::{GpioBank, Gpr},
+    pin::GPIO_ALT,
+    DriveStrength, Pad, PadConfig, Pull, Speed,
+};
+use super::register::{ReadOnly, ReadWrite, Value};
+use core::{
+    convert::Infallible,
+    marker::PhantomData,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin, StatefulOutputPin};
+
+register! {
+    /// `DR`: GPIO data register
+    dr {}
+
+    /// `GDIR`: GPIO direction register
+    gdir {}
+
+    /// `PSR`: GPIO pad status register
+    psr {}
+
+    /// `ICR1` and `ICR2`: GPIO interrupt configuration registers
+    ///
+    /// Each has two bits per pin, for pins 0 through 15 and 16 through
+    /// 31 respectively.
+    icr {}
+
+    /// `IMR`: GPIO interrupt mask register
+    imr {}
+
+    /// `ISR`: GPIO interrupt status register
+    isr {}
+
+    /// `EDGE_SEL`: GPIO edge select register
+    edge_sel {}
+}
+
+#[repr(C)]
+struct GpioRegs {
+    dr: ReadWrite<dr::Register>,
+    gdir: ReadWrite<gdir::Register>,
+    psr: ReadOnly<psr::Register>,
+    icr: [ReadWrite<icr::Register>; 2],
+    imr: ReadWrite<imr::Register>,
+    isr: ReadWrite<isr::Register>,
+    edge_sel: ReadWrite<edge_sel::Register>,
+    _reserved: [u32; 25],
+    dr_set: ReadWrite<dr::Register>,
+    dr_clear: ReadWrite<dr::Register>,
+    dr_toggle: ReadWrite<dr::Register>,
+}
+
+/// The GPIO function of a pad
+///
+/// This is implemented for every pad type in
+/// [`iomuxc::pin`](../iomuxc/pin/index.html).
+///
+/// # Safety
+/// `BANK` and `BIT` must name the GPIO reached by selecting the GPIO
+/// ALT function of this pad.
+pub unsafe trait GpioPad: Pad {
+    /// The standard-speed bank holding this pad's GPIO
+    const BANK: GpioBank;
+
+    /// The bit within that bank
+    const BIT: u32;
+}
+
+/// Marker type for a pin configured as an input
+pub struct Input<PULL> {
+    _pull: PhantomData<PULL>,
+}
+
+/// Marker type for a pin configured as an output
+pub struct Output<DRIVE> {
+    _drive: PhantomData<DRIVE>,
+}
+
+/// An input with a 22K Ohm pull up
+pub struct PullUp;
+
+/// An input with a 100K Ohm pull down
+pub struct PullDown;
+
+/// An input with neither a pull resistor nor the keeper
+pub struct Floating;
+
+/// An output which drives both high and low
+pub struct PushPull;
+
+/// An output which drives low, and floats when set high
+pub struct OpenDrain;
+
+/// The pull resistor used by an input mode
+pub trait PullMode {
+    #[doc(hidden)]
+    const PULL: Pull;
+}
+
+impl PullMode for PullUp {
+    const PULL: Pull = Pull::Up22k;
+}
+
+impl PullMode for PullDown {
+    const PULL: Pull = Pull::Down100k;
+}
+
+impl PullMode for Floating {
+    const PULL: Pull = Pull::Disabled;
+}
+
+/// The output driver used by an output mode
+pub trait DriveMode {
+    #[doc(hidden)]
+    const OPEN_DRAIN: bool;
+}
+
+impl DriveMode for PushPull {
+    const OPEN_DRAIN: bool = false;
+}
+
+impl DriveMode for OpenDrain {
+    const OPEN_DRAIN: bool = true;
+}
+
+/// The condition which raises a GPIO interrupt
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Edge {
+    /// Continuously while the pin is low
+    LowLevel,
+    /// Continuously while the pin is high
+    HighLevel,
+    /// When the pin goes from low to high
+    Rising,
+    /// When the pin goes from high to low
+    Falling,
+    /// On both rising and falling edges
+    Both,
+}
+
+/// A handler for one pin's interrupt
+pub type InterruptHandler = fn();
+
+// These are only used to build `HANDLERS`, and never shared.
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_HANDLER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_HANDLERS: [AtomicPtr<()>; 32] = [NO_HANDLER; 32];
+
+/// The registered interrupt handler for each pin of each fast bank
+static HANDLERS: [[AtomicPtr<()>; 32]; 4] = [NO_HANDLERS; 4];
+
+/// Handle the shared `GPIO6_7_8_9` interrupt
+///
+/// For every pin with a pending, enabled interrupt, this clears the
+/// interrupt and then calls the pin's registered handler, if there is
+/// one. Clearing it first means an edge which arrives while the handler
+/// runs raises the interrupt again, rather than being lost. It should
+/// be called from the interrupt handler for `GPIO6_7_8_9`.
+///
+/// A pin set to [`Edge::LowLevel`] or [`Edge::HighLevel`] raises its
+/// interrupt again for as long as the level holds, so its handler must
+/// remove the cause, or mask the interrupt, before returning.
+pub fn dispatch_interrupts() {
+    for (bank, handlers) in HANDLERS.iter().enumerate() {
+        let regs = unsafe { bank_regs(bank) };
+        let pending = regs.isr.read().bits() & regs.imr.read().bits();
+        for (bit, handler) in handlers.iter().enumerate() {
+            let mask = 1 << bit;
+            if pending & mask == 0 {
+                continue;
+            }
+            regs.isr.write(Value::from_bits(mask));
+            let handler = handler.load(Ordering::Acquire);
+            if !handler.is_null() {
+                let handler = unsafe { core::mem::transmute::<*mut (), InterruptHandler>(handler) };
+                handler();
+            }
+        }
+    }
+}
+
+/// The registers of the fast bank at `index`, where `GPIO6` is 0
+///
+/// # Safety
+/// The registers are shared by every pin in the bank, so only the
+/// atomic aliases and the write-one-to-clear `ISR` may be written
+/// outside a critical section.
+unsafe fn bank_regs(index: usize) -> &'static mut GpioRegs {
+    &mut *((0x4200_0000 + 0x4000 * index) as *mut GpioRegs)
+}
+
+/// A pad in use as a GPIO
+pub struct GpioPin<P, MODE> {
+    pad: P,
+    _mode: PhantomData<MODE>,
+}
+
+/// Pins from the same fast GPIO bank, used together
+///
+/// Bit `i` of each value written or read corresponds to the `i`th pin
+/// given to [`PortGroup::new`].
+pub struct PortGroup<MODE, const N: usize> {
+    pins: [AnyPin<MODE>; N],
+    bank: usize,
+}
+
+/// Indicates an error occured while forming a [`PortGroup`]
+#[derive(Debug)]
+pub enum PortError {
+    /// The pins are spread over more than one GPIO bank
+    DifferentBanks,
+}
+
+/// A GPIO whose pad is only known at runtime
+///
+/// This is made with [`GpioPin::erase`], and keeps the pin's mode in
+/// its type. Since the pad type is gone, the pin can't be freed back
+/// to its pad or changed to another mode.
+pub struct AnyPin<MODE> {
+    bank: usize,
+    mask: u32,
+    _mode: PhantomData<MODE>,
+}
+
+/// Conversions from a pad into a [`GpioPin`]
+///
+/// Each conversion switches the pad to its GPIO function and routes it
+/// to the fast GPIO bank.
+pub trait IntoGpio: GpioPad + Sized {
+    /// Convert this pad into an input with a pull up
+    fn into_pull_up_input(self, gpr: &mut Gpr) -> GpioPin<Self, Input<PullUp>> {
+        GpioPin::new(self, gpr).into_input()
+    }
+
+    /// Convert this pad into an input with a pull down
+    fn into_pull_down_input(self, gpr: &mut Gpr) -> GpioPin<Self, Input<PullDown>> {
+        GpioPin::new(self, gpr).into_input()
+    }
+
+    /// Convert this pad into an input with no pull resistor
+    fn into_floating_input(self, gpr: &mut Gpr) -> GpioPin<Self, Input<Floating>> {
+        GpioPin::new(self, gpr).into_input()
+    }
+
+    /// Convert this pad into a push-pull output, initially low
+    fn into_push_pull_output(self, gpr: &mut Gpr) -> GpioPin<Self, Output<PushPull>> {
+        GpioPin::new(self, gpr).into_output()
+    }
+
+    /// Convert this pad into an open-drain output, initially low
+    fn into_open_drain_output(self, gpr: &mut Gpr) -> GpioPin<Self, Output<OpenDrain>> {
+        GpioPin::new(self, gpr).into_output()
+    }
+}
+
+impl<P: GpioPad> IntoGpio for P {}
+
+impl<P: GpioPad> GpioPin<P, ()> {
+    fn new(pad: P, gpr: &mut Gpr) -> GpioPin<P, ()> {
+        let fast = gpr.fast_gpio(P::BANK);
+        gpr.set_fast_gpio(P::BANK, fast | GpioPin::<P, ()>::mask());
+        unsafe {
+            core::ptr::write_volatile(P::MUX as *mut u32, GPIO_ALT);
+        }
+        GpioPin {
+            pad,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<P: GpioPad, MODE> GpioPin<P, MODE> {
+    fn mask() -> u32 {
+        1 << P::BIT
+    }
+
+    /// # Safety
+    /// The returned registers are shared by every pin in the bank, so
+    /// only this pin's bit may be changed, and only with the atomic
+    /// aliases or inside a critical section.
+    unsafe fn regs() -> &'static mut GpioRegs {
+        bank_regs(P::BANK as usize)
+    }
+
+    fn set_direction(&mut self, output: bool) {
+        let mask = Self::mask();
+        critical_section::with(|_| unsafe {
+            Self::regs().gdir.modify(|r| {
+                let bits = if output {
+                    r.bits() | mask
+                } else {
+                    r.bits() & !mask
+                };
+                *r = Value::from_bits(bits);
+            });
+        });
+    }
+
+    /// Mask and clear this pin's interrupt, and forget its handler
+    fn reset_interrupt(&mut self) {
+        let mask = Self::mask();
+        critical_section::with(|_| unsafe {
+            let regs = Self::regs();
+            regs.imr.modify(|r| *r = Value::from_bits(r.bits() & !mask));
+            regs.isr.write(Value::from_bits(mask));
+        });
+        HANDLERS[P::BANK as usize][P::BIT as usize].store(ptr::null_mut(), Ordering::Release);
+    }
+
+    fn into_input<PULL: PullMode>(mut self) -> GpioPin<P, Input<PULL>> {
+        self.reset_interrupt();
+        self.pad.configure(
+            PadConfig::new()
+                .drive_strength(DriveStrength::R0_7)
+                .pull(PULL::PULL)
+                .hysteresis(true),
+        );
+        self.set_direction(false);
+        GpioPin {
+            pad: self.pad,
+            _mode: PhantomData,
+        }
+    }
+
+    fn into_output<DRIVE: DriveMode>(mut self) -> GpioPin<P, Output<DRIVE>> {
+        self.reset_interrupt();
+        self.pad.configure(
+            PadConfig::new()
+                .drive_strength(DriveStrength::R0_7)
+                .speed(Speed::Medium)
+                .pull(Pull::Disabled)
+                .open_drain(DRIVE::OPEN_DRAIN),
+        );
+        unsafe {
+            Self::regs().dr_clear.write(Value::from_bits(Self::mask()));
+        }
+        self.set_direction(true);
+        GpioPin {
+            pad: self.pad,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Turn this pin into an input with a pull up
+    pub fn into_pull_up_input(self) -> GpioPin<P, Input<PullUp>> {
+        self.into_input()
+    }
+
+    /// Turn this pin into an input with a pull down
+    pub fn into_pull_down_input(self) -> GpioPin<P, Input<PullDown>> {
+        self.into_input()
+    }
+
+    /// Turn this pin into an input with no pull resistor
+    pub fn into_floating_input(self) -> GpioPin<P, Input<Floating>> {
+        self.into_input()
+    }
+
+    /// Turn this pin into a push-pull output, initially low
+    pub fn into_push_pull_output(self) -> GpioPin<P, Output<PushPull>> {
+        self.into_output()
+    }
+
+    /// Turn this pin into an open-drain output, initially low
+    pub fn into_open_drain_output(self) -> GpioPin<P, Output<OpenDrain>> {
+        self.into_output()
+    }
+
+    /// Forget which pad this pin is, so that it can be stored
+    /// alongside pins of other pads
+    pub fn erase(self) -> AnyPin<MODE> {
+        AnyPin {
+            bank: P::BANK as usize,
+            mask: Self::mask(),
+            _mode: PhantomData,
+        }
+    }
+
+    /// Stop using this pin as a GPIO
+    ///
+    /// The pin is made an input with its interrupt disabled, and given
+    /// the reset pad configuration. It stays routed to the fast GPIO
+    /// bank.
+    pub fn free(mut self) -> P {
+        self.reset_interrupt();
+        self.set_direction(false);
+        self.pad.configure(PadConfig::new());
+        self.pad
+    }
+}
+
+impl<P: GpioPad, PULL> GpioPin<P, Input<PULL>> {
+    /// Whether the pin is being driven high
+    pub fn is_high(&self) -> bool {
+        unsafe { Self::regs().psr.read().bits() & Self::mask() != 0 }
+    }
+
+    /// Whether the pin is being driven low
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+
+    /// Raise an interrupt on `edge`
+    ///
+    /// Any interrupt left pending from before is cleared first.
+    pub fn enable_interrupt(&mut self, edge: Edge) {
+        let mask = Self::mask();
+        let (icr, both) = match edge {
+            Edge::LowLevel => (0, false),
+            Edge::HighLevel => (1, false),
+            Edge::Rising => (2, false),
+            Edge::Falling => (3, false),
+            Edge::Both => (0, true),
+        };
+        let shift = 2 * (P::BIT % 16);
+        critical_section::with(|_| unsafe {
+            let regs = Self::regs();
+            regs.imr.modify(|r| *r = Value::from_bits(r.bits() & !mask));
+            regs.icr[P::BIT as usize / 16].modify(|r| {
+                *r = Value::from_bits(r.bits() & !(3 << shift) | icr << shift);
+            });
+            regs.edge_sel.modify(|r| {
+                let bits = if both {
+                    r.bits() | mask
+                } else {
+                    r.bits() & !mask
+                };
+                *r = Value::from_bits(bits);
+            });
+            regs.isr.write(Value::from_bits(mask));
+            regs.imr.modify(|r| *r = Value::from_bits(r.bits() | mask));
+        });
+    }
+
+    /// Stop raising interrupts for this pin
+    pub fn disable_interrupt(&mut self) {
+        let mask = Self::mask();
+        critical_section::with(|_| unsafe {
+            Self::regs()
+                .imr
+                .modify(|r| *r = Value::from_bits(r.bits() & !mask));
+        });
+    }
+
+    /// Whether this pin's interrupt condition has occurred
+    ///
+    /// This is tracked even while the interrupt is disabled, so it can
+    /// be polled.
+    pub fn interrupt_pending(&self) -> bool {
+        unsafe { Self::regs().isr.read().bits() & Self::mask() != 0 }
+    }
+
+    /// Clear this pin's pending interrupt
+    pub fn clear_interrupt(&mut self) {
+        unsafe {
+            Self::regs().isr.write(Value::from_bits(Self::mask()));
+        }
+    }
+
+    /// Register the function [`dispatch_interrupts`] calls for this
+    /// pin, replacing any earlier one
+    pub fn set_interrupt_handler(&mut self, handler: Option<InterruptHandler>) {
+        let handler = handler.map_or(ptr::null_mut(), |handler| handler as *mut ());
+        HANDLERS[P::BANK as usize][P::BIT as usize].store(handler, Ordering::Release);
+    }
+}
+
+impl<P: GpioPad, DRIVE> GpioPin<P, Output<DRIVE>> {
+    /// Drive the pin high
+    ///
+    /// An open-drain output floats instead.
+    pub fn set_high(&mut self) {
+        unsafe {
+            Self::regs().dr_set.write(Value::from_bits(Self::mask()));
+        }
+    }
+
+    /// Drive the pin low
+    pub fn set_low(&mut self) {
+        unsafe {
+            Self::regs().dr_clear.write(Value::from_bits(Self::mask()));
+        }
+    }
+
+    /// Flip the pin between high and low
+    pub fn toggle(&mut self) {
+        unsafe {
+            Self::regs().dr_toggle.write(Value::from_bits(Self::mask()));
+        }
+    }
+
+    /// Whether the pin has been set high
+    pub fn is_set_high(&self) -> bool {
+        unsafe { Self::regs().dr.read().bits() & Self::mask() != 0 }
+    }
+
+    /// Whether the pin has been set low
+    pub fn is_set_low(&self) -> bool {
+        !self.is_set_high()
+    }
+
+    /// Whether the pin is actually high
+    ///
+    /// For an open-drain output this reflects the level on the wire,
+    /// which another device may be holding low.
+    pub fn is_high(&self) -> bool {
+        unsafe { Self::regs().psr.read().bits() & Self::mask() != 0 }
+    }
+}
+
+impl<MODE> AnyPin<MODE> {
+    unsafe fn regs(&self) -> &'static mut GpioRegs {
+        bank_regs(self.bank)
+    }
+
+    fn read_psr(&self) -> bool {
+        unsafe { self.regs().psr.read().bits() & self.mask != 0 }
+    }
+}
+
+impl<PULL> AnyPin<Input<PULL>> {
+    /// Whether the pin is being driven high
+    pub fn is_high(&self) -> bool {
+        self.read_psr()
+    }
+
+    /// Whether the pin is being driven low
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+}
+
+impl<DRIVE> AnyPin<Output<DRIVE>> {
+    /// Drive the pin high
+    ///
+    /// An open-drain output floats instead.
+    pub fn set_high(&mut self) {
+        unsafe {
+            self.regs().dr_set.write(Value::from_bits(self.mask));
+        }
+    }
+
+    /// Drive the pin low
+    pub fn set_low(&mut self) {
+        unsafe {
+            self.regs().dr_clear.write(Value::from_bits(self.mask));
+        }
+    }
+
+    /// Flip the pin between high and low
+    pub fn toggle(&mut self) {
+        unsafe {
+            self.regs().dr_toggle.write(Value::from_bits(self.mask));
+        }
+    }
+
+    /// Whether the pin has been set high
+    pub fn is_set_high(&self) -> bool {
+        unsafe { self.regs().dr.read().bits() & self.mask != 0 }
+    }
+
+    /// Whether the pin has been set low
+    pub fn is_set_low(&self) -> bool {
+        !self.is_set_high()
+    }
+
+    /// Whether the pin is actually high
+    ///
+    /// For an open-drain output this reflects the level on the wire,
+    /// which another device may be holding low.
+    pub fn is_high(&self) -> bool {
+        self.read_psr()
+    }
+}
+
+impl<MODE, const N: usize> PortGroup<MODE, N> {
+    /// Gather `pins` into a group
+    ///
+    /// # Errors
+    /// Returns the pins along with [`PortError::DifferentBanks`] if
+    /// they are not all in the same GPIO bank.
+    pub fn new(pins: [AnyPin<MODE>; N]) -> Result<Self, ([AnyPin<MODE>; N], PortError)> {
+        let bank = pins.first().map_or(0, |pin| pin.bank);
+        if pins.iter().any(|pin| pin.bank != bank) {
+            return Err((pins, PortError::DifferentBanks));
+        }
+        Ok(PortGroup { pins, bank })
+    }
+
+    /// Split the group back into its pins
+    pub fn release(self) -> [AnyPin<MODE>; N] {
+        self.pins
+    }
+
+    unsafe fn regs(&self) -> &'static mut GpioRegs {
+        bank_regs(self.bank)
+    }
+
+    /// The bank bits of the pins selected by the set bits of `value`
+    fn bank_bits(&self, value: u32) -> u32 {
+        self.pins
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| value & (1 << i) != 0)
+            .fold(0, |bits, (_, pin)| bits | pin.mask)
+    }
+
+    /// Read the level of every pin
+    pub fn read(&self) -> u32 {
+        let psr = unsafe { self.regs().psr.read().bits() };
+        self.pins
+            .iter()
+            .enumerate()
+            .filter(|(_, pin)| psr & pin.mask != 0)
+            .fold(0, |value, (i, _)| value | 1 << i)
+    }
+}
+
+impl<DRIVE, const N: usize> PortGroup<Output<DRIVE>, N> {
+    /// Set every pin from the matching bit of `value`
+    ///
+    /// Every pin changes at once, in a single store to `DR`, so a
+    /// parallel bus never shows a mix of old and new values. Other
+    /// pins of the bank are left alone, as the store happens inside a
+    /// critical section.
+    pub fn write(&mut self, value: u32) {
+        let mask = self.bank_bits(!0);
+        let high = self.bank_bits(value);
+        critical_section::with(|_| unsafe {
+            self.regs()
+                .dr
+                .modify(|r| *r = Value::from_bits(r.bits() & !mask | high));
+        });
+    }
+
+    /// Toggle the pins selected by the set bits of `value`
+    pub fn toggle(&mut self, value: u32) {
+        let bits = self.bank_bits(value);
+        unsafe {
+            self.regs().dr_toggle.write(Value::from_bits(bits));
+        }
+    }
+}
+
+impl<P, MODE> ErrorType for GpioPin<P, MODE> {
+    type Error = Infallible;
+}
+
+impl<P: GpioPad, PULL> InputPin for GpioPin<P, Input<PULL>> {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(GpioPin::<P, Input<PULL>>::is_high(self))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(GpioPin::is_low(self))
+    }
+}
+
+impl<P: GpioPad, DRIVE> OutputPin for GpioPin<P, Output<DRIVE>> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        GpioPin::set_low(self);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        GpioPin::set_high(self);
+        Ok(())
+    }
+}
+
+impl<P: GpioPad, DRIVE> StatefulOutputPin for GpioPin<P, Output<DRIVE>> {
+    fn is_set_high(&mut self) -> Result<bool, Infallible> {
+        Ok(GpioPin::is_set_high(self))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Infallible> {
+        Ok(GpioPin::is_set_low(self))
+    }
+
+    fn toggle(&mut self) -> Result<(), Infallible> {
+        GpioPin::toggle(self);
+        Ok(())
+    }
+}
+
+impl<MODE> ErrorType for AnyPin<MODE> {
+    type Error = Infallible;
+}
+
+impl<PULL> InputPin for AnyPin<Input<PULL>> {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(AnyPin::<Input<PULL>>::is_high(self))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(AnyPin::is_low(self))
+    }
+}
+
+impl<DRIVE> OutputPin for AnyPin<Output<DRIVE>> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        AnyPin::set_low(self);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        AnyPin::set_high(self);
+        Ok(())
+    }
+}
+
+impl<DRIVE> StatefulOutputPin for AnyPin<Output<DRIVE>> {
+    fn is_set_high(&mut self) -> Result<bool, Infallible> {
+        Ok(AnyPin::is_set_high(self))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Infallible> {
+        Ok(AnyPin::is_set_low(self))
+    }
+
+    fn toggle(&mut self) -> Result<(), Infallible> {
+        AnyPin::toggle(self);
+        Ok(())
+    }
+}
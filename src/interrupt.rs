@@ -0,0 +1,77 @@
+//! Interrupts and the vector table
+//!
+//! The vector table holds the 16 Cortex-M7 exceptions followed by the
+//! 160 RT1062 interrupts. Each entry calls the function named after it,
+//! such as `LPUART6` for [`Interrupt::LpUart6`] or `SysTick` for the
+//! system timer. A program handles one simply by defining it:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub unsafe extern "C" fn GPIO6_7_8_9() {
+//!     teensy40::gpio::dispatch_interrupts();
+//! }
+//! ```
+//!
+//! Anything left undefined goes to `DefaultHandler`, which spins
+//! forever unless the program defines that too. The core exceptions
+//! are named `NonMaskableInt`, `HardFault`, `MemoryManagement`,
+//! `BusFault`, `UsageFault`, `SVCall`, `DebugMonitor`, `PendSV` and
+//! `SysTick`. The faults among them instead default to the handler
+//! in [`fault`](super::fault).
+//!
+//! A handler's name isn't checked against its signature, so take care
+//! to spell it exactly as in [`Interrupt`]'s documentation, and give it
+//! the type of [`Handler`].
+
+/// The type of an exception or interrupt handler
+pub type Handler = unsafe extern "C" fn();
+
+macro_rules! interrupts {
+    ($(
+        $(#[$meta:meta])*
+        $irq:literal => $handler:ident: $variant:ident,
+    )*) => {
+        /// An RT1062 interrupt, numbered as in the NVIC
+        ///
+        /// Each variant's documentation gives the name of its
+        /// handler.
+        #[derive(PartialEq, Eq, Copy, Clone, Debug)]
+        pub enum Interrupt {
+            $(
+                $(#[$meta])*
+                $variant = $irq,
+            )*
+        }
+
+        extern "C" {
+            $(fn $handler();)*
+        }
+
+        /// The interrupt half of the vector table
+        pub(crate) const VECTORS: [Option<Handler>; 160] = {
+            let mut vectors: [Option<Handler>; 160] = [None; 160];
+            $(vectors[$irq] = Some($handler);)*
+            vectors
+        };
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/interrupts.rs"));
+
+impl Interrupt {
+    /// The interrupt's number in the NVIC
+    pub fn number(self) -> usize {
+        self as usize
+    }
+}
+
+/// The handler for anything which has no handler of its own
+///
+/// The linker script points `DefaultHandler` here unless the program
+/// defines it.
+#[no_mangle]
+pub extern "C" fn DefaultHandler_() {
+    loop {
+        core::hint::spin_loop();
+    }
+}
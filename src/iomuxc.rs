@@ -1,10 +1,26 @@
 //! The Input/Output Multiplexer Controller
 //!
 //! The IOMUXC is responsible for mapping pins to individual hardware
-//! I/O units.
+//! I/O units, and for the electrical configuration of each pad.
+
+use super::register::{ReadWrite, Value};
+
+register! {
+    /// `SW_PAD_CTL_PAD_*`: Pad Control Register
+    sw_pad_ctl_pad {
+        SRE: Field<SlewRate> = 0..1,
+        DSE: Field<DriveStrength> = 3..6,
+        SPEED: Field<Speed> = 6..8,
+        ODE: Bit = 11,
+        PKE: Bit = 12,
+        PUE: Bit = 13,
+        PUS: Field = 14..16,
+        HYS: Bit = 16,
+    }
+}
 
 pub struct Iomuxc {
-    _private: (),
+    gpr: gpr::Gpr,
 }
 
 #[derive(Debug)]
@@ -20,7 +36,9 @@ impl super::ccm::ClockGated for Iomuxc {
     }
 
     unsafe fn enable() -> Self {
-        Iomuxc { _private: () }
+        Iomuxc {
+            gpr: gpr::Gpr::new(),
+        }
     }
 
     fn disable(self) {}
@@ -30,43 +48,279 @@ impl Iomuxc {
     pub fn get_pin<P: Pin>(&self) -> Result<P, PinError> {
         P::new(self)
     }
+
+    /// Return a pin taken with [`get_pin`](Iomuxc::get_pin)
+    pub fn release_pin<P: Pin>(&self, pin: P) {
+        pin.release()
+    }
+
+    /// Get the [General Purpose Registers](gpr::Gpr) immutably
+    pub fn gpr(&self) -> &gpr::Gpr {
+        &self.gpr
+    }
+
+    /// Get the [General Purpose Registers](gpr::Gpr) mutably
+    pub fn gpr_mut(&mut self) -> &mut gpr::Gpr {
+        &mut self.gpr
+    }
 }
 
 pub trait Pin: Sized {
     fn new(_: &Iomuxc) -> Result<Self, PinError>;
+
+    /// Return this pin to the pool, so that it can be taken again
+    /// with [`Iomuxc::get_pin`]
+    ///
+    /// The pad is switched back to its GPIO function and given the
+    /// reset [`PadConfig`].
+    fn release(self);
+}
+
+/// Register addresses for a pad
+///
+/// This is implemented for every pad type in [`pin`], both in its
+/// initial state and after any `into_*` conversion.
+///
+/// # Safety
+/// `MUX` and `PAD` must be the addresses of the mux and pad control
+/// registers for the one pad owned by the implementing type.
+pub unsafe trait Pad {
+    /// The address of this pad's `SW_MUX_CTL_PAD` register
+    const MUX: usize;
+
+    /// The address of this pad's `SW_PAD_CTL_PAD` register
+    const PAD: usize;
+
+    /// Apply an electrical configuration to this pad
+    ///
+    /// This can be done at any point, whether or not the pad has been
+    /// switched to a peripheral.
+    fn configure(&mut self, config: PadConfig) {
+        let pad_ctl = unsafe { &mut *(Self::PAD as *mut ReadWrite<sw_pad_ctl_pad::Register>) };
+        pad_ctl.write(config.value);
+    }
+}
+
+/// The electrical configuration of a pad
+///
+/// This starts from the reset configuration of the pads on the Teensy,
+/// and each setting can be changed with a builder method. For example,
+/// an I2C pad needs open-drain output with a pull-up:
+///
+/// ```ignore
+/// let config = PadConfig::new()
+///     .open_drain(true)
+///     .pull(Pull::Up22k);
+/// ```
+///
+/// while a fast SPI clock wants full drive and speed:
+///
+/// ```ignore
+/// let config = PadConfig::new()
+///     .drive_strength(DriveStrength::R0_7)
+///     .speed(Speed::Max)
+///     .slew_rate(SlewRate::Fast);
+/// ```
+///
+/// The configuration is applied to a pad with [`Pad::configure`].
+#[derive(Copy, Clone)]
+pub struct PadConfig {
+    value: Value<sw_pad_ctl_pad::Register>,
+}
+
+/// How quickly a pad's output transitions
+#[derive(PartialEq, Copy, Clone)]
+pub enum SlewRate {
+    Slow,
+    Fast,
+}
+
+#[doc(hidden)]
+impl From<u32> for SlewRate {
+    fn from(v: u32) -> SlewRate {
+        match v {
+            0 => SlewRate::Slow,
+            1 => SlewRate::Fast,
+            _ => panic!("Invalid value for slew rate"),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl From<SlewRate> for u32 {
+    fn from(v: SlewRate) -> u32 {
+        match v {
+            SlewRate::Slow => 0,
+            SlewRate::Fast => 1,
+        }
+    }
 }
 
-pub mod pin {
-    use core::sync::atomic::{AtomicBool, Ordering};
+/// The output impedance of a pad's driver
+///
+/// `R0` is 150 Ohm at 3.3V. The other settings divide it, so `R0_7` is
+/// the strongest drive.
+#[derive(PartialEq, Copy, Clone)]
+pub enum DriveStrength {
+    /// The output driver is disabled
+    Disabled,
+    R0,
+    R0_2,
+    R0_3,
+    R0_4,
+    R0_5,
+    R0_6,
+    R0_7,
+}
 
-    pub struct GpioAdB0_02 {
-        _private: (),
+#[doc(hidden)]
+impl From<u32> for DriveStrength {
+    fn from(v: u32) -> DriveStrength {
+        match v {
+            0 => DriveStrength::Disabled,
+            1 => DriveStrength::R0,
+            2 => DriveStrength::R0_2,
+            3 => DriveStrength::R0_3,
+            4 => DriveStrength::R0_4,
+            5 => DriveStrength::R0_5,
+            6 => DriveStrength::R0_6,
+            7 => DriveStrength::R0_7,
+            _ => panic!("Invalid value for drive strength"),
+        }
     }
+}
 
-    pub struct GpioAdB0_02LpUartTx {
-        _private: (),
+#[doc(hidden)]
+impl From<DriveStrength> for u32 {
+    fn from(v: DriveStrength) -> u32 {
+        match v {
+            DriveStrength::Disabled => 0,
+            DriveStrength::R0 => 1,
+            DriveStrength::R0_2 => 2,
+            DriveStrength::R0_3 => 3,
+            DriveStrength::R0_4 => 4,
+            DriveStrength::R0_5 => 5,
+            DriveStrength::R0_6 => 6,
+            DriveStrength::R0_7 => 7,
+        }
     }
+}
+
+/// The operating frequency range of a pad
+#[derive(PartialEq, Copy, Clone)]
+pub enum Speed {
+    /// 50MHz
+    Low,
+    /// 100MHz
+    Medium,
+    /// 200MHz
+    Max,
+}
 
-    impl GpioAdB0_02 {
-        pub fn into_lpuart_tx(self) -> GpioAdB0_02LpUartTx {
-            unsafe {
-                core::ptr::write_volatile(0x401F_80C4 as *mut u32, 2);
-            }
-            GpioAdB0_02LpUartTx { _private: () }
+#[doc(hidden)]
+impl From<u32> for Speed {
+    fn from(v: u32) -> Speed {
+        // Both 1 and 2 select the medium speed
+        match v {
+            0 => Speed::Low,
+            1 | 2 => Speed::Medium,
+            3 => Speed::Max,
+            _ => panic!("Invalid value for pad speed"),
         }
     }
+}
 
-    static GPIO_AD_B0_02_INIT: AtomicBool = AtomicBool::new(false);
-    impl super::Pin for GpioAdB0_02 {
-        fn new(_: &super::Iomuxc) -> Result<Self, super::PinError> {
-            let was_init = GPIO_AD_B0_02_INIT.swap(true, Ordering::Acquire);
-            if was_init {
-                Err(super::PinError::InUse)
-            } else {
-                Ok(GpioAdB0_02 { _private: () })
-            }
+#[doc(hidden)]
+impl From<Speed> for u32 {
+    fn from(v: Speed) -> u32 {
+        match v {
+            Speed::Low => 0,
+            Speed::Medium => 2,
+            Speed::Max => 3,
         }
     }
+}
 
-    impl super::super::lpuart::LpUart6Tx for GpioAdB0_02LpUartTx {}
+/// The pull resistor or keeper attached to a pad
+#[derive(PartialEq, Copy, Clone)]
+pub enum Pull {
+    /// Neither a pull resistor nor the keeper is attached
+    Disabled,
+    /// The keeper holds the last driven level once the pad is released
+    Keeper,
+    /// 100K Ohm pull down
+    Down100k,
+    /// 47K Ohm pull up
+    Up47k,
+    /// 100K Ohm pull up
+    Up100k,
+    /// 22K Ohm pull up
+    Up22k,
 }
+
+impl PadConfig {
+    /// The reset configuration: keeper enabled, medium speed and
+    /// `R0_6` drive, with slow slew, no hysteresis and push-pull output.
+    pub fn new() -> PadConfig {
+        PadConfig {
+            value: Value::from_bits(0x10B0),
+        }
+    }
+
+    /// Set the slew rate
+    pub fn slew_rate(mut self, slew_rate: SlewRate) -> PadConfig {
+        self.value.set(sw_pad_ctl_pad::SRE, slew_rate);
+        self
+    }
+
+    /// Set the drive strength
+    pub fn drive_strength(mut self, drive_strength: DriveStrength) -> PadConfig {
+        self.value.set(sw_pad_ctl_pad::DSE, drive_strength);
+        self
+    }
+
+    /// Set the speed
+    pub fn speed(mut self, speed: Speed) -> PadConfig {
+        self.value.set(sw_pad_ctl_pad::SPEED, speed);
+        self
+    }
+
+    /// Enable or disable open-drain output
+    pub fn open_drain(mut self, open_drain: bool) -> PadConfig {
+        self.value.set_bit(sw_pad_ctl_pad::ODE, open_drain);
+        self
+    }
+
+    /// Select the pull resistor or keeper
+    pub fn pull(mut self, pull: Pull) -> PadConfig {
+        let (pke, pue, pus) = match pull {
+            Pull::Disabled => (false, false, 0),
+            Pull::Keeper => (true, false, 0),
+            Pull::Down100k => (true, true, 0),
+            Pull::Up47k => (true, true, 1),
+            Pull::Up100k => (true, true, 2),
+            Pull::Up22k => (true, true, 3),
+        };
+        self.value
+            .set_bit(sw_pad_ctl_pad::PKE, pke)
+            .set_bit(sw_pad_ctl_pad::PUE, pue)
+            .set(sw_pad_ctl_pad::PUS, pus);
+        self
+    }
+
+    /// Enable or disable the Schmitt trigger on the input
+    pub fn hysteresis(mut self, hysteresis: bool) -> PadConfig {
+        self.value.set_bit(sw_pad_ctl_pad::HYS, hysteresis);
+        self
+    }
+}
+
+impl Default for PadConfig {
+    fn default() -> PadConfig {
+        PadConfig::new()
+    }
+}
+
+pub mod gpr;
+pub mod pin;
+pub mod signal;
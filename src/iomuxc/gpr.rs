@@ -0,0 +1,507 @@
+//! IOMUXC General Purpose Registers
+//!
+//! Despite living in the IOMUXC, the GPR block has little to do with
+//! pads. It holds chip-wide configuration, of which the [`Gpr`] handle
+//! covers:
+//!
+//! * which GPIO banks are reached through the fast AHB path
+//! * how the FlexRAM banks are split between OCRAM, ITCM and DTCM
+//! * the direction of the SAI and ENET reference clocks
+//! * the GPT reference clock
+//! * the FlexSPI address remap
+//!
+//! The remaining fields, such as the SAI `MCLK` selection and the MQS
+//! setup, are left for the drivers of those peripherals.
+//!
+//! The [`Gpr`] handle is owned by the [`Iomuxc`](../struct.Iomuxc.html).
+
+use super::super::register::{Bit, ReadWrite};
+use core::ops::Range;
+
+register! {
+    /// `GPR0`: Reserved
+    gpr0 {}
+
+    /// `GPR1`: Clock direction and selection for SAI and ENET
+    gpr1 {
+        ENET1_CLK_SEL: Bit = 13,
+        ENET2_CLK_SEL: Bit = 14,
+        ENET1_TX_CLK_DIR: Bit = 17,
+        ENET2_TX_CLK_DIR: Bit = 18,
+        SAI1_MCLK_DIR: Bit = 19,
+        SAI2_MCLK_DIR: Bit = 20,
+        SAI3_MCLK_DIR: Bit = 21,
+    }
+
+    /// `GPR2`: Memory power saving and MQS configuration
+    gpr2 {}
+
+    /// `GPR3`: OCRAM and DCP configuration
+    gpr3 {}
+
+    /// `GPR4`: Peripheral stop requests
+    gpr4 {}
+
+    /// `GPR5`: Watchdog and GPT configuration
+    gpr5 {
+        VREF_1M_CLK_GPT1: Bit = 28,
+        VREF_1M_CLK_GPT2: Bit = 29,
+    }
+
+    /// `GPR6`: QTIMER and XBAR input selection
+    gpr6 {}
+
+    /// `GPR7`: Peripheral stop acknowledgements
+    gpr7 {}
+
+    /// `GPR8`: LPI2C and LPSPI doze and stop modes
+    gpr8 {}
+
+    /// `GPR9`: Reserved
+    gpr9 {}
+
+    /// `GPR10`: Security and lock configuration
+    gpr10 {}
+
+    /// `GPR11`: OCRAM TrustZone configuration
+    gpr11 {}
+
+    /// `GPR12`: FlexIO and ACMP stop modes
+    gpr12 {}
+
+    /// `GPR13`: AXI cache attributes
+    gpr13 {}
+
+    /// `GPR14`: TCM sizes and ACMP configuration
+    gpr14 {
+        CM7_CFGITCMSZ: Field<TcmSize> = 16..20,
+        CM7_CFGDTCMSZ: Field<TcmSize> = 20..24,
+    }
+
+    /// `GPR15`: Reserved
+    gpr15 {}
+
+    /// `GPR16`: TCM enables and FlexRAM configuration source
+    gpr16 {
+        INIT_ITCM_EN: Bit = 0,
+        INIT_DTCM_EN: Bit = 1,
+        FLEXRAM_BANK_CFG_SEL: Bit = 2,
+    }
+
+    /// `GPR17`: FlexRAM bank configuration
+    gpr17 {
+        FLEXRAM_BANK_CFG: Field = 0..32,
+    }
+
+    /// `GPR18`: Reserved
+    gpr18 {}
+
+    /// `GPR19`: Reserved
+    gpr19 {}
+
+    /// `GPR20`: Reserved
+    gpr20 {}
+
+    /// `GPR21`: Reserved
+    gpr21 {}
+
+    /// `GPR22`: Reserved
+    gpr22 {}
+
+    /// `GPR23`: Reserved
+    gpr23 {}
+
+    /// `GPR24`: Reserved
+    gpr24 {}
+
+    /// `GPR25`: Reserved
+    gpr25 {}
+
+    /// `GPR26` through `GPR29`: GPIO fast path selection
+    ///
+    /// Each bit routes one pin of GPIO1 through GPIO4 to the matching
+    /// pin of the fast GPIO6 through GPIO9 instead.
+    gpio_mux {
+        GPIO_SEL: Field = 0..32,
+    }
+
+    /// `GPR30`: FlexSPI remap start address
+    gpr30 {
+        FLEXSPI_REMAP_ADDR_START: Field = 12..32,
+    }
+
+    /// `GPR31`: FlexSPI remap end address
+    gpr31 {
+        FLEXSPI_REMAP_ADDR_END: Field = 12..32,
+    }
+
+    /// `GPR32`: FlexSPI remap offset
+    gpr32 {
+        FLEXSPI_REMAP_ADDR_OFFSET: Field = 12..32,
+    }
+
+    /// `GPR33`: OCOTP lock
+    gpr33 {}
+
+    /// `GPR34`: SIP test mux
+    gpr34 {}
+}
+
+#[repr(C)]
+struct GprRegs {
+    gpr0: ReadWrite<gpr0::Register>,
+    gpr1: ReadWrite<gpr1::Register>,
+    gpr2: ReadWrite<gpr2::Register>,
+    gpr3: ReadWrite<gpr3::Register>,
+    gpr4: ReadWrite<gpr4::Register>,
+    gpr5: ReadWrite<gpr5::Register>,
+    gpr6: ReadWrite<gpr6::Register>,
+    gpr7: ReadWrite<gpr7::Register>,
+    gpr8: ReadWrite<gpr8::Register>,
+    gpr9: ReadWrite<gpr9::Register>,
+    gpr10: ReadWrite<gpr10::Register>,
+    gpr11: ReadWrite<gpr11::Register>,
+    gpr12: ReadWrite<gpr12::Register>,
+    gpr13: ReadWrite<gpr13::Register>,
+    gpr14: ReadWrite<gpr14::Register>,
+    gpr15: ReadWrite<gpr15::Register>,
+    gpr16: ReadWrite<gpr16::Register>,
+    gpr17: ReadWrite<gpr17::Register>,
+    gpr18: ReadWrite<gpr18::Register>,
+    gpr19: ReadWrite<gpr19::Register>,
+    gpr20: ReadWrite<gpr20::Register>,
+    gpr21: ReadWrite<gpr21::Register>,
+    gpr22: ReadWrite<gpr22::Register>,
+    gpr23: ReadWrite<gpr23::Register>,
+    gpr24: ReadWrite<gpr24::Register>,
+    gpr25: ReadWrite<gpr25::Register>,
+    gpio_mux: [ReadWrite<gpio_mux::Register>; 4],
+    gpr30: ReadWrite<gpr30::Register>,
+    gpr31: ReadWrite<gpr31::Register>,
+    gpr32: ReadWrite<gpr32::Register>,
+    gpr33: ReadWrite<gpr33::Register>,
+    gpr34: ReadWrite<gpr34::Register>,
+}
+
+/// The IOMUXC General Purpose Registers
+pub struct Gpr {
+    regs: &'static mut GprRegs,
+}
+
+/// One of the standard-speed GPIO banks
+///
+/// Each of these shares its pins with a fast GPIO bank on the AHB
+/// bus, and [`Gpr::set_fast_gpio`] chooses which of the two drives
+/// each pin.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum GpioBank {
+    /// `GPIO1`, paired with `GPIO6`
+    Gpio1,
+    /// `GPIO2`, paired with `GPIO7`
+    Gpio2,
+    /// `GPIO3`, paired with `GPIO8`
+    Gpio3,
+    /// `GPIO4`, paired with `GPIO9`
+    Gpio4,
+}
+
+/// The use of a single 32KB FlexRAM bank
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum FlexRamBank {
+    Unused,
+    Ocram,
+    Dtcm,
+    Itcm,
+}
+
+#[doc(hidden)]
+impl From<u32> for FlexRamBank {
+    fn from(v: u32) -> FlexRamBank {
+        match v {
+            0 => FlexRamBank::Unused,
+            1 => FlexRamBank::Ocram,
+            2 => FlexRamBank::Dtcm,
+            3 => FlexRamBank::Itcm,
+            _ => panic!("Invalid value for FlexRAM bank"),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl From<FlexRamBank> for u32 {
+    fn from(v: FlexRamBank) -> u32 {
+        match v {
+            FlexRamBank::Unused => 0,
+            FlexRamBank::Ocram => 1,
+            FlexRamBank::Dtcm => 2,
+            FlexRamBank::Itcm => 3,
+        }
+    }
+}
+
+/// The size of the ITCM or DTCM seen by the Cortex-M7
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TcmSize {
+    Disabled,
+    Kb4,
+    Kb8,
+    Kb16,
+    Kb32,
+    Kb64,
+    Kb128,
+    Kb256,
+    Kb512,
+}
+
+#[doc(hidden)]
+impl From<u32> for TcmSize {
+    fn from(v: u32) -> TcmSize {
+        match v {
+            0 => TcmSize::Disabled,
+            3 => TcmSize::Kb4,
+            4 => TcmSize::Kb8,
+            5 => TcmSize::Kb16,
+            6 => TcmSize::Kb32,
+            7 => TcmSize::Kb64,
+            8 => TcmSize::Kb128,
+            9 => TcmSize::Kb256,
+            10 => TcmSize::Kb512,
+            _ => panic!("Invalid value for TCM size"),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl From<TcmSize> for u32 {
+    fn from(v: TcmSize) -> u32 {
+        match v {
+            TcmSize::Disabled => 0,
+            TcmSize::Kb4 => 3,
+            TcmSize::Kb8 => 4,
+            TcmSize::Kb16 => 5,
+            TcmSize::Kb32 => 6,
+            TcmSize::Kb64 => 7,
+            TcmSize::Kb128 => 8,
+            TcmSize::Kb256 => 9,
+            TcmSize::Kb512 => 10,
+        }
+    }
+}
+
+/// Whether a clock pin is driven by the chip or from outside
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ClockDirection {
+    Input,
+    Output,
+}
+
+/// A SAI instance
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Sai {
+    Sai1,
+    Sai2,
+    Sai3,
+}
+
+/// An ENET instance
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Enet {
+    Enet1,
+    Enet2,
+}
+
+impl Gpr {
+    /// # Safety
+    /// Only the [`Iomuxc`](../struct.Iomuxc.html) may hold a `Gpr`.
+    pub(crate) unsafe fn new() -> Gpr {
+        Gpr {
+            regs: &mut *(0x400A_C000 as *mut GprRegs),
+        }
+    }
+
+    /// The pins of `bank` which are routed to its fast GPIO bank
+    pub fn fast_gpio(&self, bank: GpioBank) -> u32 {
+        self.regs.gpio_mux[bank as usize].get(gpio_mux::GPIO_SEL)
+    }
+
+    /// Route the pins in `mask` to the fast GPIO bank paired with
+    /// `bank`, and the rest of its pins to `bank` itself
+    pub fn set_fast_gpio(&mut self, bank: GpioBank, mask: u32) {
+        self.regs.gpio_mux[bank as usize].set(gpio_mux::GPIO_SEL, mask);
+    }
+
+    /// The current use of each FlexRAM bank
+    ///
+    /// This is only meaningful once the configuration has been taken
+    /// from `GPR17` rather than the fuses, as it is by
+    /// [`set_flexram_banks`](Gpr::set_flexram_banks).
+    pub fn flexram_banks(&self) -> [FlexRamBank; 16] {
+        let config = self.regs.gpr17.get(gpr17::FLEXRAM_BANK_CFG);
+        let mut banks = [FlexRamBank::Unused; 16];
+        for (i, bank) in banks.iter_mut().enumerate() {
+            *bank = FlexRamBank::from((config >> (2 * i)) & 3);
+        }
+        banks
+    }
+
+    /// Whether the FlexRAM configuration comes from `GPR17`, rather
+    /// than from the fuses
+    pub fn flexram_banks_overridden(&self) -> bool {
+        self.regs.gpr16.is_set(gpr16::FLEXRAM_BANK_CFG_SEL)
+    }
+
+    /// Repartition the FlexRAM banks
+    ///
+    /// # Safety
+    /// Any bank which changes use loses its contents, and is moved to
+    /// a different address. Nothing, including the stack, may live in
+    /// such a bank when this is called.
+    pub unsafe fn set_flexram_banks(&mut self, banks: [FlexRamBank; 16]) {
+        let config = banks
+            .iter()
+            .enumerate()
+            .fold(0, |config, (i, &bank)| config | u32::from(bank) << (2 * i));
+        self.regs.gpr17.set(gpr17::FLEXRAM_BANK_CFG, config);
+        self.regs.gpr16.set_bit(gpr16::FLEXRAM_BANK_CFG_SEL, true);
+    }
+
+    /// The sizes of the ITCM and DTCM, as `(itcm, dtcm)`
+    pub fn tcm_sizes(&self) -> (TcmSize, TcmSize) {
+        let gpr14 = self.regs.gpr14.read();
+        (
+            gpr14.get(gpr14::CM7_CFGITCMSZ),
+            gpr14.get(gpr14::CM7_CFGDTCMSZ),
+        )
+    }
+
+    /// Set the sizes of the ITCM and DTCM
+    ///
+    /// # Safety
+    /// The sizes must match the FlexRAM banks given to each, and
+    /// nothing may live past the end of a TCM which shrinks.
+    pub unsafe fn set_tcm_sizes(&mut self, itcm: TcmSize, dtcm: TcmSize) {
+        self.regs.gpr14.modify(|r| {
+            r.set(gpr14::CM7_CFGITCMSZ, itcm)
+                .set(gpr14::CM7_CFGDTCMSZ, dtcm);
+        });
+    }
+
+    /// Enable or disable the ITCM and DTCM, as `(itcm, dtcm)`
+    ///
+    /// # Safety
+    /// Nothing may live in a TCM which is disabled.
+    pub unsafe fn set_tcm_enabled(&mut self, itcm: bool, dtcm: bool) {
+        self.regs.gpr16.modify(|r| {
+            r.set_bit(gpr16::INIT_ITCM_EN, itcm)
+                .set_bit(gpr16::INIT_DTCM_EN, dtcm);
+        });
+    }
+
+    /// The direction of a SAI's `MCLK` pin
+    pub fn sai_mclk_direction(&self, sai: Sai) -> ClockDirection {
+        direction(self.regs.gpr1.is_set(sai_mclk_dir(sai)))
+    }
+
+    /// Set the direction of a SAI's `MCLK` pin
+    pub fn set_sai_mclk_direction(&mut self, sai: Sai, direction: ClockDirection) {
+        self.regs
+            .gpr1
+            .set_bit(sai_mclk_dir(sai), direction == ClockDirection::Output);
+    }
+
+    /// The direction of an ENET's `TX_CLK` pin
+    pub fn enet_tx_clock_direction(&self, enet: Enet) -> ClockDirection {
+        direction(self.regs.gpr1.is_set(enet_tx_clk_dir(enet)))
+    }
+
+    /// Set the direction of an ENET's `TX_CLK` pin
+    pub fn set_enet_tx_clock_direction(&mut self, enet: Enet, direction: ClockDirection) {
+        self.regs
+            .gpr1
+            .set_bit(enet_tx_clk_dir(enet), direction == ClockDirection::Output);
+    }
+
+    /// Select whether an ENET's reference clock comes from its
+    /// `REF_CLK` pin (`false`) or from the ENET PLL (`true`)
+    pub fn set_enet_clock_internal(&mut self, enet: Enet, internal: bool) {
+        let bit = match enet {
+            Enet::Enet1 => gpr1::ENET1_CLK_SEL,
+            Enet::Enet2 => gpr1::ENET2_CLK_SEL,
+        };
+        self.regs.gpr1.set_bit(bit, internal);
+    }
+
+    /// The FlexSPI addresses which are remapped, and the offset added
+    /// to them, as `(range, offset)`
+    pub fn flexspi_remap(&self) -> (Range<u32>, u32) {
+        let start = self.regs.gpr30.get(gpr30::FLEXSPI_REMAP_ADDR_START);
+        let end = self.regs.gpr31.get(gpr31::FLEXSPI_REMAP_ADDR_END);
+        let offset = self.regs.gpr32.get(gpr32::FLEXSPI_REMAP_ADDR_OFFSET);
+        (start * REMAP_ALIGN..end * REMAP_ALIGN, offset * REMAP_ALIGN)
+    }
+
+    /// Redirect accesses to the FlexSPI addresses in `range` to
+    /// `offset` bytes further on
+    ///
+    /// This lets a program run a second image from flash at the
+    /// addresses it was linked for. An empty range turns the remap
+    /// off.
+    ///
+    /// # Safety
+    /// Everything in the range moves, so no code or data in use may
+    /// live there.
+    ///
+    /// # Panics
+    /// The start and end of the range, and the offset, must be
+    /// multiples of 4K.
+    pub unsafe fn set_flexspi_remap(&mut self, range: Range<u32>, offset: u32) {
+        assert!((range.start | range.end | offset) & (REMAP_ALIGN - 1) == 0);
+        // Empty the range first, so nothing is remapped by a partial
+        // setting
+        self.regs.gpr31.set(gpr31::FLEXSPI_REMAP_ADDR_END, 0);
+        self.regs
+            .gpr32
+            .set(gpr32::FLEXSPI_REMAP_ADDR_OFFSET, offset / REMAP_ALIGN);
+        self.regs
+            .gpr30
+            .set(gpr30::FLEXSPI_REMAP_ADDR_START, range.start / REMAP_ALIGN);
+        self.regs
+            .gpr31
+            .set(gpr31::FLEXSPI_REMAP_ADDR_END, range.end / REMAP_ALIGN);
+    }
+
+    /// Select whether GPT1 and GPT2 count the 1MHz reference clock
+    /// rather than their usual input
+    pub fn set_gpt_1mhz_reference(&mut self, gpt1: bool, gpt2: bool) {
+        self.regs.gpr5.modify(|r| {
+            r.set_bit(gpr5::VREF_1M_CLK_GPT1, gpt1)
+                .set_bit(gpr5::VREF_1M_CLK_GPT2, gpt2);
+        });
+    }
+}
+
+/// The granularity of the FlexSPI remap addresses
+const REMAP_ALIGN: u32 = 0x1000;
+
+fn direction(output: bool) -> ClockDirection {
+    if output {
+        ClockDirection::Output
+    } else {
+        ClockDirection::Input
+    }
+}
+
+fn sai_mclk_dir(sai: Sai) -> Bit<gpr1::Register> {
+    match sai {
+        Sai::Sai1 => gpr1::SAI1_MCLK_DIR,
+        Sai::Sai2 => gpr1::SAI2_MCLK_DIR,
+        Sai::Sai3 => gpr1::SAI3_MCLK_DIR,
+    }
+}
+
+fn enet_tx_clk_dir(enet: Enet) -> Bit<gpr1::Register> {
+    match enet {
+        Enet::Enet1 => gpr1::ENET1_TX_CLK_DIR,
+        Enet::Enet2 => gpr1::ENET2_TX_CLK_DIR,
+    }
+}
@@ -0,0 +1,149 @@
+//! Typed pads
+//!
+//! Every pad broken out on the Teensy 4.0 has a type here, which can
+//! be taken once from [`Iomuxc::get_pin`](../struct.Iomuxc.html#method.get_pin).
+//! Each pad type has an `into_*` method for every one of its ALT
+//! functions. These switch the pad's mux and return a new type which
+//! implements the marker trait for that peripheral signal. The
+//! converted type can be turned back into the pad type with `free`,
+//! and the pad can be given back with [`Pin::release`](../trait.Pin.html#tymethod.release).
+//!
+//! When an input signal can be routed from more than one pad, the
+//! conversion also programs the signal's `*_SELECT_INPUT` daisy
+//! register, so that the peripheral listens to this pad. `free` puts
+//! the daisy back to its reset value of 0, unless another pad has
+//! since taken the signal over.
+//!
+//! The pad types are generated by the build script from the pin-mux
+//! table in `pinmux.csv`. To add a pad or correct an ALT function,
+//! edit that table rather than this file.
+//!
+//! The USB host pads on the bottom of the board are wired straight to
+//! the second USB PHY rather than through the IOMUXC, so they have no
+//! entry here.
+
+use super::{super::gpio::GpioPad, gpr::GpioBank, Pad, PadConfig};
+use core::cell::Cell;
+use critical_section::Mutex;
+
+/// The ALT function selecting GPIO, which is the reset function of
+/// every pad broken out on the Teensy
+pub(crate) const GPIO_ALT: u32 = 5;
+
+/// Put a pad back into its reset state
+///
+/// # Safety
+/// The caller must own the pad.
+unsafe fn reset<P: Pad>(pad: &mut P) {
+    core::ptr::write_volatile(P::MUX as *mut u32, GPIO_ALT);
+    pad.configure(PadConfig::new());
+}
+
+macro_rules! pads {
+    ($(
+        $(#[$meta:meta])*
+        $pad:ident = $init:ident, $mux:literal, $pad_ctl:literal, $bank:ident[$bit:literal] {
+            $(
+                $alt:literal => $into:ident -> $muxed:ident: $signal:path, $doc:literal,
+                $(daisy $daisy:literal = $daisy_value:literal,)?
+            )*
+        }
+    )*) => {$(
+        $(#[$meta])*
+        pub struct $pad {
+            _private: (),
+        }
+
+        static $init: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+        impl super::Pin for $pad {
+            fn new(_: &super::Iomuxc) -> Result<Self, super::PinError> {
+                let was_init = critical_section::with(|cs| $init.borrow(cs).replace(true));
+                if was_init {
+                    Err(super::PinError::InUse)
+                } else {
+                    Ok($pad { _private: () })
+                }
+            }
+
+            fn release(mut self) {
+                unsafe {
+                    reset(&mut self);
+                }
+                critical_section::with(|cs| $init.borrow(cs).set(false));
+            }
+        }
+
+        unsafe impl super::Pad for $pad {
+            const MUX: usize = $mux;
+            const PAD: usize = $pad_ctl;
+        }
+
+        unsafe impl GpioPad for $pad {
+            const BANK: GpioBank = GpioBank::$bank;
+            const BIT: u32 = $bit;
+        }
+
+        impl $pad {
+            /// Take this pad without checking whether it is in use
+            ///
+            /// This also turns on the IOMUXC clock, which startup
+            /// leaves off, so that the pad can be muxed even if the
+            /// program never enabled the [`Iomuxc`](super::Iomuxc).
+            ///
+            /// # Safety
+            /// Nothing else may be using the pad.
+            pub unsafe fn steal() -> $pad {
+                super::super::ccm::steal_clock_gate::<super::Iomuxc>();
+                $pad { _private: () }
+            }
+
+            $(
+                #[doc = $doc]
+                pub fn $into(self) -> $muxed {
+                    unsafe {
+                        core::ptr::write_volatile($mux as *mut u32, $alt);
+                        $(core::ptr::write_volatile($daisy as *mut u32, $daisy_value);)?
+                    }
+                    $muxed { _private: () }
+                }
+            )*
+        }
+
+        $(
+            #[doc = $doc]
+            pub struct $muxed {
+                _private: (),
+            }
+
+            unsafe impl super::Pad for $muxed {
+                const MUX: usize = $mux;
+                const PAD: usize = $pad_ctl;
+            }
+
+            impl $muxed {
+                /// Switch this pad back to GPIO with the reset pad
+                /// configuration
+                ///
+                /// If the conversion routed an input signal to this
+                /// pad, the signal's daisy goes back to its reset value
+                /// as well, unless it now points at another pad.
+                pub fn free(mut self) -> $pad {
+                    unsafe {
+                        reset(&mut self);
+                        $(
+                            if core::ptr::read_volatile($daisy as *const u32) == $daisy_value {
+                                core::ptr::write_volatile($daisy as *mut u32, 0);
+                            }
+                        )?
+                    }
+                    $pad { _private: () }
+                }
+            }
+
+            impl $signal for $muxed {}
+        )*
+    )*};
+}
+
+include!(concat!(env!("OUT_DIR"), "/pins.rs"));
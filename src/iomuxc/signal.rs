@@ -0,0 +1,8 @@
+//! Peripheral signal marker traits
+//!
+//! Each trait here marks a pad type which has been muxed to the named
+//! signal. Peripherals which have a driver in this crate define their
+//! marker traits alongside that driver instead, as
+//! [`LpUart6Tx`](../../lpuart/trait.LpUart6Tx.html) does.
+
+include!(concat!(env!("OUT_DIR"), "/signals.rs"));
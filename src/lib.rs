@@ -11,9 +11,9 @@
 //! #![features(asm)]
 //!
 //! use teensy40::{
+//!    board,
 //!    ccm::Ccm,
 //!    iomuxc::Iomuxc
-//!    iomuxc::pin
 //!    lpuart::LpUart6
 //!    debug,
 //! };
@@ -34,10 +34,8 @@
 //!     uart_clock.set_divisor(1);
 //!
 //!     let iomux = ccm.enable::<iomuxc::Iomuxc>().unwrap();
-//!     let tx_pin = iomux
-//!         .get_pin::<iomuxc::pin::GpioAdB0_02>()
-//!         .unwrap()
-//!         .into_lpuart_tx();
+//!     let pins = board::Pins::new(&iomux).unwrap();
+//!     let tx_pin = pins.p1.into_lpuart_tx();
 //!
 //!     let mut uart = ccm.enable::<lpuart::LpUart6<(), ()>>().unwrap();
 //!     uart.set_clocks(250, 10);
@@ -53,8 +51,7 @@
 //! #[panic_handler]
 //! fn teensy_panic(_: &core::panic::PanicInfo) -> ! {
 //!     unsafe {
-//!         debug::enable();
-//!         debug::led();
+//!         debug::steal_led().on();
 //!         sleep();
 //!     }
 //! }
@@ -64,10 +61,27 @@
 #![no_std]
 #![feature(const_transmute)]
 
+#[cfg(all(feature = "panic-led", feature = "panic-uart"))]
+compile_error!("Only one of the `panic-led` and `panic-uart` features can be enabled");
+
 mod bootdata;
+#[cfg(feature = "panic-led")]
+mod panic_led;
 mod startup;
 
+#[macro_use]
+pub mod register;
+
+pub mod board;
+pub mod cache;
 pub mod ccm;
 pub mod debug;
+pub mod fault;
+pub mod gpio;
+pub mod interrupt;
 pub mod iomuxc;
 pub mod lpuart;
+pub mod mpu;
+pub mod nvic;
+#[cfg(feature = "panic-uart")]
+pub mod panic_uart;
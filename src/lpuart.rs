@@ -3,23 +3,90 @@
 //! The `LPUART` modules in the i.MX RT1062 provide the most basic
 //! serial data transfer.
 
-use bit_field::BitField;
-use volatile::{ReadOnly, Volatile};
+use super::register::{ReadOnly, ReadWrite};
 
-#[repr(C, packed)]
+register! {
+    /// `VERID`: Version ID Register
+    verid {}
+
+    /// `PARAM`: Parameter Register
+    param {}
+
+    /// `GLOBAL`: LPUART Global Register
+    global {
+        /// Holds every register other than `GLOBAL` in reset
+        RST: Bit = 1,
+    }
+
+    /// `PINCFG`: LPUART Pin Configuration Register
+    pincfg {}
+
+    /// `BAUD`: LPUART Baud Rate Register
+    baud {
+        SBR: Field = 0..13,
+        OSR: Field = 24..29,
+    }
+
+    /// `STAT`: LPUART Status Register
+    stat {
+        /// Set when there is no pending data to be sent
+        TC: Bit = 22,
+    }
+
+    /// `CTRL`: LPUART Control Register
+    ctrl {
+        RE: Bit = 18,
+        TE: Bit = 19,
+    }
+
+    /// `DATA`: LPUART Data Register
+    data {
+        DATA: Field = 0..10,
+    }
+
+    /// `MATCH`: LPUART Match Address Register
+    r#match {}
+
+    /// `MODIR`: LPUART Modem IrDA Register
+    modir {}
+
+    /// `FIFO`: LPUART FIFO Register
+    fifo {}
+
+    /// `WATER`: LPUART Watermark Register
+    water {}
+}
+
+#[repr(C)]
 struct LpUartRegs {
-    verid: ReadOnly<u32>,
-    param: ReadOnly<u32>,
-    global: Volatile<u32>,
-    pincfg: Volatile<u32>,
-    baud: Volatile<u32>,
-    stat: Volatile<u32>,
-    ctrl: Volatile<u32>,
-    data: Volatile<u32>,
-    r#match: Volatile<u32>,
-    modir: Volatile<u32>,
-    fifo: Volatile<u32>,
-    water: Volatile<u32>,
+    verid: ReadOnly<verid::Register>,
+    param: ReadOnly<param::Register>,
+    global: ReadWrite<global::Register>,
+    pincfg: ReadWrite<pincfg::Register>,
+    baud: ReadWrite<baud::Register>,
+    stat: ReadWrite<stat::Register>,
+    ctrl: ReadWrite<ctrl::Register>,
+    data: ReadWrite<data::Register>,
+    r#match: ReadWrite<r#match::Register>,
+    modir: ReadWrite<modir::Register>,
+    fifo: ReadWrite<fifo::Register>,
+    water: ReadWrite<water::Register>,
+}
+
+/// A UART with no pins assigned, which can transmit from `Tx`
+///
+/// This is implemented for every UART, so that code such as
+/// [`panic_uart::steal`](../panic_uart/fn.steal.html) can work with
+/// any of them.
+pub trait Transmitter<Tx>: super::ccm::ClockGated {
+    /// The UART once `Tx` is its transmit pin
+    type Output: core::fmt::Write;
+
+    /// Set the baud rate, as with the UART's own `set_clocks`
+    fn set_clocks(&mut self, divisor: u32, oversample: u32);
+
+    /// Set the transmit pin, as with the UART's own `set_tx`
+    fn set_tx(self, tx: Tx) -> Self::Output;
 }
 
 macro_rules! uart {
@@ -90,7 +157,12 @@ macro_rules! uart {
                 }
             }
 
-            fn disable(self) {}
+            /// Reset the UART, returning every register to its
+            /// default value
+            fn disable(self) {
+                self.regs.global.set_bit(global::RST, true);
+                self.regs.global.set_bit(global::RST, false);
+            }
         }
 
         impl $name<(), ()> {
@@ -99,13 +171,22 @@ macro_rules! uart {
             /// This can only be done for a UART which has not had has
             /// a TX or RX pin assigned.
             pub fn set_clocks(&mut self, divisor: u32, oversample: u32) {
-                unsafe {
-                    self.regs.baud.update(|r| {
-                        // baud[osr]
-                        r.set_bits(24..29, oversample - 1);
-                        r.set_bits(0..13, divisor);
-                    });
-                }
+                self.regs.baud.modify(|r| {
+                    r.set(baud::OSR, oversample - 1);
+                    r.set(baud::SBR, divisor);
+                });
+            }
+        }
+
+        impl<Tx: $tx_pin> Transmitter<Tx> for $name<(), ()> {
+            type Output = $name<Tx, ()>;
+
+            fn set_clocks(&mut self, divisor: u32, oversample: u32) {
+                $name::set_clocks(self, divisor, oversample);
+            }
+
+            fn set_tx(self, tx: Tx) -> $name<Tx, ()> {
+                $name::set_tx(self, tx).0
             }
         }
 
@@ -124,12 +205,7 @@ macro_rules! uart {
                 let rx = self.rx;
                 let old_tx = self.tx;
 
-                unsafe {
-                    regs.ctrl.update(|r| {
-                        // ctrl[te]
-                        r.set_bit(19, true);
-                    });
-                }
+                regs.ctrl.set_bit(ctrl::TE, true);
 
                 ($name { regs, tx, rx }, old_tx)
             }
@@ -148,6 +224,30 @@ macro_rules! uart {
                 let old_rx = self.rx;
                 ($name { regs, tx, rx }, old_rx)
             }
+
+            /// Release both pins
+            ///
+            /// This disables transmit and recieve, and returns the UART
+            /// with no pins assigned, along with the old pins. The
+            /// UART can then be given new pins, or disabled with
+            /// [`Ccm::disable`](../ccm/struct.Ccm.html#method.disable).
+            pub fn release(self) -> ($name<(), ()>, T, R) {
+                let regs = self.regs;
+
+                regs.ctrl.modify(|r| {
+                    r.set_bit(ctrl::TE, false).set_bit(ctrl::RE, false);
+                });
+
+                (
+                    $name {
+                        regs,
+                        tx: (),
+                        rx: (),
+                    },
+                    self.tx,
+                    self.rx,
+                )
+            }
         }
 
         impl<T, R> $name<T, R>
@@ -160,13 +260,11 @@ macro_rules! uart {
             /// set. This method will block until the UART has
             /// completed transmission of the byte.
             pub fn send(&mut self, byte: u8) {
-                unsafe {
-                    self.regs.data.write(u32::from(byte));
+                self.regs.data.write_with(|r| {
+                    r.set(data::DATA, u32::from(byte));
+                });
 
-                    // state[tc]
-                    // TC is set when there is no pending data to be sent.
-                    while !self.regs.stat.read().get_bit(22) {}
-                }
+                while !self.regs.stat.is_set(stat::TC) {}
             }
         }
 
@@ -0,0 +1,275 @@
+//! The Cortex-M7 Memory Protection Unit
+//!
+//! The MPU decides the caching, ordering and access rules for each
+//! area of memory. Startup programs a default map into the low
+//! regions, before the caches are turned on:
+//!
+//! | Region | Area                    | Memory      | Access     |
+//! |--------|-------------------------|-------------|------------|
+//! | 0      | ITCM                    | Normal      | Read-write |
+//! | 1      | DTCM                    | Normal      | Read-write, no execute |
+//! | 2      | OCRAM                   | Write-back  | Read-write, no execute |
+//! | 3      | Peripherals             | Device      | Read-write, no execute |
+//! | 4      | FlexSPI flash           | Write-back  | Read-only  |
+//! | 5      | The first 32 bytes at 0 | Strongly ordered | None  |
+//!
+//! When regions overlap, the higher number wins, so region 5 makes
+//! null pointer accesses fault despite the ITCM underneath. Anything
+//! outside every region keeps the Cortex-M7's default attributes.
+//!
+//! Regions [`FIRST_FREE_REGION`] up to 14 are left for the program:
+//!
+//! ```ignore
+//! // Keep the DMA buffers in the top 64K of OCRAM out of the cache
+//! let region = mpu::Region::new(0x2027_0000, 0x1_0000)
+//!     .memory(mpu::MemoryType::NonCacheable);
+//! unsafe {
+//!     mpu::set_region(mpu::FIRST_FREE_REGION, region);
+//! }
+//! ```
+//!
+//! Region 15 is taken by the stack guard when the `stack-guard`
+//! feature is enabled.
+
+use super::register::{ReadWrite, Value};
+use core::arch::asm;
+
+register! {
+    /// `MPU_CTRL`: MPU Control Register
+    ctrl {
+        ENABLE: Bit = 0,
+        HFNMIENA: Bit = 1,
+        PRIVDEFENA: Bit = 2,
+    }
+
+    /// `MPU_RNR`: MPU Region Number Register
+    rnr {
+        REGION: Field = 0..8,
+    }
+
+    /// `MPU_RBAR`: MPU Region Base Address Register
+    rbar {}
+
+    /// `MPU_RASR`: MPU Region Attribute and Size Register
+    rasr {
+        ENABLE: Bit = 0,
+        SIZE: Field = 1..6,
+        B: Bit = 16,
+        C: Bit = 17,
+        S: Bit = 18,
+        TEX: Field = 19..22,
+        AP: Field<Access> = 24..27,
+        XN: Bit = 28,
+    }
+}
+
+#[repr(C)]
+struct MpuRegs {
+    _typer: u32,
+    ctrl: ReadWrite<ctrl::Register>,
+    rnr: ReadWrite<rnr::Register>,
+    rbar: ReadWrite<rbar::Register>,
+    rasr: ReadWrite<rasr::Register>,
+}
+
+/// # Safety
+/// Programming a region takes several writes, so it must happen in a
+/// critical section. Changing the attributes of memory in use can
+/// also break the program in any number of ways.
+unsafe fn regs() -> &'static mut MpuRegs {
+    &mut *(0xE000_ED90 as *mut MpuRegs)
+}
+
+/// The number of regions the Cortex-M7 MPU has
+pub const REGIONS: u8 = 16;
+
+/// The first region not used by the default map
+pub const FIRST_FREE_REGION: u8 = 6;
+
+/// What may be done with a region's memory
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Access {
+    NoAccess,
+    ReadWrite,
+    ReadOnly,
+}
+
+#[doc(hidden)]
+impl From<u32> for Access {
+    fn from(v: u32) -> Access {
+        match v {
+            0 => Access::NoAccess,
+            3 => Access::ReadWrite,
+            6 => Access::ReadOnly,
+            _ => panic!("Invalid value for access"),
+        }
+    }
+}
+
+#[doc(hidden)]
+impl From<Access> for u32 {
+    fn from(v: Access) -> u32 {
+        match v {
+            Access::NoAccess => 0,
+            Access::ReadWrite => 3,
+            Access::ReadOnly => 6,
+        }
+    }
+}
+
+/// How a region's memory is cached and ordered
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MemoryType {
+    /// Every access happens in program order, and completes before
+    /// the next one starts
+    StronglyOrdered,
+    /// Accesses happen in program order, for peripheral registers
+    Device,
+    /// Normal memory which is never cached
+    NonCacheable,
+    /// Normal memory where reads are cached, and writes go straight
+    /// through to memory
+    WriteThrough,
+    /// Normal memory where reads and writes are cached
+    WriteBack,
+}
+
+/// A region for the MPU
+///
+/// Regions are built up from [`Region::new`], which gives read-write,
+/// write-back memory that can't be executed:
+///
+/// ```ignore
+/// let flash = Region::new(0x6000_0000, 0x100_0000)
+///     .access(Access::ReadOnly)
+///     .executable(true);
+/// ```
+#[derive(Copy, Clone)]
+pub struct Region {
+    base: u32,
+    attributes: Value<rasr::Register>,
+}
+
+impl Region {
+    /// A region of `size` bytes starting at `base`
+    ///
+    /// # Panics
+    /// The size must be a power of two of at least 32 bytes, and the
+    /// base must be a multiple of it.
+    pub fn new(base: u32, size: u32) -> Region {
+        assert!(size >= 32 && size.is_power_of_two());
+        assert!(base & (size - 1) == 0);
+        let mut attributes = Value::from_bits(0);
+        attributes
+            .set_bit(rasr::ENABLE, true)
+            .set(rasr::SIZE, size.trailing_zeros() - 1);
+        Region { base, attributes }
+            .memory(MemoryType::WriteBack)
+            .access(Access::ReadWrite)
+            .executable(false)
+    }
+
+    /// Set how the memory is cached and ordered
+    pub fn memory(mut self, memory: MemoryType) -> Region {
+        let (tex, c, b) = match memory {
+            MemoryType::StronglyOrdered => (0, false, false),
+            MemoryType::Device => (0, false, true),
+            MemoryType::NonCacheable => (1, false, false),
+            MemoryType::WriteThrough => (0, true, false),
+            MemoryType::WriteBack => (1, true, true),
+        };
+        self.attributes
+            .set(rasr::TEX, tex)
+            .set_bit(rasr::C, c)
+            .set_bit(rasr::B, b);
+        self
+    }
+
+    /// Set what may be done with the memory
+    pub fn access(mut self, access: Access) -> Region {
+        self.attributes.set(rasr::AP, access);
+        self
+    }
+
+    /// Allow or forbid running code from the memory
+    pub fn executable(mut self, executable: bool) -> Region {
+        self.attributes.set_bit(rasr::XN, !executable);
+        self
+    }
+}
+
+/// Program `region` into the MPU as region `number`
+///
+/// # Safety
+/// Changing the attributes of memory in use, such as the stack or the
+/// code calling this, can break the program in any number of ways.
+/// Memory which becomes cacheable, or stops being so, should be
+/// cleaned from the cache first.
+///
+/// # Panics
+/// `number` must be less than [`REGIONS`].
+pub unsafe fn set_region(number: u8, region: Region) {
+    assert!(number < REGIONS);
+    critical_section::with(|_| {
+        let regs = regs();
+        regs.rnr.write_with(|r| {
+            r.set(rnr::REGION, u32::from(number));
+        });
+        regs.rbar.write(Value::from_bits(region.base));
+        regs.rasr.write(Value::from_bits(region.attributes.bits()));
+        asm!("dsb", "isb");
+    });
+}
+
+/// Turn off region `number`
+///
+/// # Safety
+/// See [`set_region`].
+///
+/// # Panics
+/// `number` must be less than [`REGIONS`].
+pub unsafe fn clear_region(number: u8) {
+    assert!(number < REGIONS);
+    critical_section::with(|_| {
+        let regs = regs();
+        regs.rnr.write_with(|r| {
+            r.set(rnr::REGION, u32::from(number));
+        });
+        regs.rasr.write(Value::from_bits(0));
+        asm!("dsb", "isb");
+    });
+}
+
+/// Program the default map, and turn on the MPU
+///
+/// # Safety
+/// This must only run at startup, before the caches are turned on.
+pub(crate) unsafe fn enable() {
+    let map = [
+        Region::new(0x0000_0000, 0x8_0000)
+            .memory(MemoryType::NonCacheable)
+            .executable(true),
+        Region::new(0x2000_0000, 0x8_0000).memory(MemoryType::NonCacheable),
+        Region::new(0x2020_0000, 0x10_0000),
+        Region::new(0x4000_0000, 0x400_0000).memory(MemoryType::Device),
+        Region::new(0x6000_0000, 0x100_0000)
+            .access(Access::ReadOnly)
+            .executable(true),
+        Region::new(0x0000_0000, 32)
+            .memory(MemoryType::StronglyOrdered)
+            .access(Access::NoAccess),
+    ];
+    for (number, &region) in map.iter().enumerate() {
+        set_region(number as u8, region);
+    }
+
+    // Keep the default map as a background for privileged code. With
+    // HFNMIENA clear, the MPU is off while HardFault and NMI run, but
+    // stays on for MemoryManagement, BusFault and UsageFault, so the
+    // fault handler sees the same map as the code which faulted
+    regs().ctrl.write_with(|r| {
+        r.set_bit(ctrl::PRIVDEFENA, true)
+            .set_bit(ctrl::ENABLE, true);
+    });
+    asm!("dsb", "isb");
+}
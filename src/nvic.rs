@@ -0,0 +1,189 @@
+//! Nested Vectored Interrupt Controller
+//!
+//! The NVIC decides which of the RT1062's [interrupts](Interrupt)
+//! reach their handlers, and in what order. An interrupt only runs its
+//! handler once [enabled](enable):
+//!
+//! ```ignore
+//! unsafe {
+//!     nvic::set_priority(Interrupt::Gpio6789, 0x80);
+//!     nvic::enable(Interrupt::Gpio6789);
+//! }
+//! ```
+//!
+//! This module also provides the [`critical_section`] implementation
+//! for the crate, which masks every interrupt through `PRIMASK` for the
+//! length of the critical section. Code which shares data with an
+//! interrupt handler can use `critical_section::with` to keep the
+//! handler out while it works.
+
+use super::{
+    interrupt::Interrupt,
+    register::{Field, ReadWrite, Value},
+};
+use core::arch::asm;
+
+register! {
+    /// `NVIC_ISER`: Interrupt Set-Enable Registers
+    iser {}
+
+    /// `NVIC_ICER`: Interrupt Clear-Enable Registers
+    icer {}
+
+    /// `NVIC_ISPR`: Interrupt Set-Pending Registers
+    ispr {}
+
+    /// `NVIC_ICPR`: Interrupt Clear-Pending Registers
+    icpr {}
+
+    /// `NVIC_IABR`: Interrupt Active Bit Registers
+    iabr {}
+
+    /// `NVIC_IPR`: Interrupt Priority Registers
+    ///
+    /// Each holds the priority of four interrupts, one per byte.
+    ipr {}
+}
+
+/// The number of 32-interrupt words the RT1062's 160 interrupts need
+const WORDS: usize = 5;
+
+#[repr(C)]
+struct NvicRegs {
+    iser: [ReadWrite<iser::Register>; WORDS],
+    _reserved0: [u32; 32 - WORDS],
+    icer: [ReadWrite<icer::Register>; WORDS],
+    _reserved1: [u32; 32 - WORDS],
+    ispr: [ReadWrite<ispr::Register>; WORDS],
+    _reserved2: [u32; 32 - WORDS],
+    icpr: [ReadWrite<icpr::Register>; WORDS],
+    _reserved3: [u32; 32 - WORDS],
+    iabr: [ReadWrite<iabr::Register>; WORDS],
+    _reserved4: [u32; 64 - WORDS],
+    ipr: [ReadWrite<ipr::Register>; 8 * WORDS],
+}
+
+/// # Safety
+/// The set and clear registers may be written freely, as they only
+/// touch the bits written as one. Anything else must only be changed
+/// inside a critical section.
+unsafe fn regs() -> &'static mut NvicRegs {
+    &mut *(0xE000_E100 as *mut NvicRegs)
+}
+
+/// The word and bit of `interrupt` in the one-bit-per-interrupt
+/// registers
+fn position(interrupt: Interrupt) -> (usize, u32) {
+    let number = interrupt.number();
+    (number / 32, 1 << (number % 32))
+}
+
+/// Let `interrupt` run its handler
+///
+/// # Safety
+/// The handler may run at any point from here on, so anything it
+/// shares with the rest of the program must already be protected,
+/// for example by a critical section.
+pub unsafe fn enable(interrupt: Interrupt) {
+    let (word, bit) = position(interrupt);
+    regs().iser[word].write(Value::from_bits(bit));
+}
+
+/// Stop `interrupt` from running its handler
+///
+/// The interrupt can still become pending while disabled.
+pub fn disable(interrupt: Interrupt) {
+    let (word, bit) = position(interrupt);
+    unsafe {
+        regs().icer[word].write(Value::from_bits(bit));
+    }
+}
+
+/// Whether `interrupt` is enabled
+pub fn is_enabled(interrupt: Interrupt) -> bool {
+    let (word, bit) = position(interrupt);
+    unsafe { regs().iser[word].read().bits() & bit != 0 }
+}
+
+/// Mark `interrupt` as pending, as if the hardware had raised it
+pub fn pend(interrupt: Interrupt) {
+    let (word, bit) = position(interrupt);
+    unsafe {
+        regs().ispr[word].write(Value::from_bits(bit));
+    }
+}
+
+/// Clear a pending `interrupt` before its handler runs
+pub fn unpend(interrupt: Interrupt) {
+    let (word, bit) = position(interrupt);
+    unsafe {
+        regs().icpr[word].write(Value::from_bits(bit));
+    }
+}
+
+/// Whether `interrupt` is waiting for its handler to run
+pub fn is_pending(interrupt: Interrupt) -> bool {
+    let (word, bit) = position(interrupt);
+    unsafe { regs().ispr[word].read().bits() & bit != 0 }
+}
+
+/// Whether the handler for `interrupt` is running
+///
+/// This includes a handler which has been preempted by a more urgent
+/// one.
+pub fn is_active(interrupt: Interrupt) -> bool {
+    let (word, bit) = position(interrupt);
+    unsafe { regs().iabr[word].read().bits() & bit != 0 }
+}
+
+/// The field of `NVIC_IPR` holding the priority of `interrupt`
+fn priority_field(interrupt: Interrupt) -> (usize, Field<ipr::Register>) {
+    let number = interrupt.number();
+    let shift = 8 * (number % 4) as u32;
+    (number / 4, Field::new(shift..shift + 8))
+}
+
+/// The priority of `interrupt`
+pub fn priority(interrupt: Interrupt) -> u8 {
+    let (word, field) = priority_field(interrupt);
+    unsafe { regs().ipr[word].get(field) as u8 }
+}
+
+/// Set the priority of `interrupt`, where lower numbers are more urgent
+///
+/// The RT1062 implements only the top four bits, giving 16 levels in
+/// steps of `0x10`. The lower bits are ignored.
+///
+/// # Safety
+/// Changing priorities can break code which relies on one handler
+/// never preempting another.
+pub unsafe fn set_priority(interrupt: Interrupt, priority: u8) {
+    let (word, field) = priority_field(interrupt);
+    critical_section::with(|_| {
+        regs().ipr[word].set(field, u32::from(priority));
+    });
+}
+
+/// Masks every interrupt with `PRIMASK`
+struct PrimaskCriticalSection;
+
+critical_section::set_impl!(PrimaskCriticalSection);
+
+// The asm blocks may touch memory as far as the compiler knows, so
+// nothing is moved into or out of the critical section.
+unsafe impl critical_section::Impl for PrimaskCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {
+        let primask: u32;
+        asm!("mrs {}, PRIMASK", out(reg) primask, options(nostack, preserves_flags));
+        asm!("cpsid i", options(nostack, preserves_flags));
+        // Only re-enable interrupts on release if they were enabled
+        // here, so that critical sections can nest
+        primask & 1 == 0
+    }
+
+    unsafe fn release(were_enabled: critical_section::RawRestoreState) {
+        if were_enabled {
+            asm!("cpsie i", options(nostack, preserves_flags));
+        }
+    }
+}
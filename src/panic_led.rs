@@ -0,0 +1,149 @@
+//! A panic handler which blinks the panic location on the LED
+//!
+//! This is enabled by the `panic-led` feature. Once a panic occurs,
+//! the orange LED repeats the following sequence forever:
+//!
+//! 1. A long two second flash, marking the start of the sequence
+//! 2. The line number of the panic, one decimal digit at a time
+//! 3. A three digit code identifying the source file
+//!
+//! Each digit is shown as that many short flashes, with ten flashes
+//! for a zero. Digits are separated by a one second pause, and the
+//! line number and file code by a two second pause.
+//!
+//! The file code is the FNV-1a hash of the file path reported by the
+//! panic, modulo 1000. Matching it up means hashing the candidate
+//! paths in the same way, for example with
+//!
+//! ```text
+//! $ python3 -c 'import sys
+//! h = 0x811c9dc5
+//! for b in sys.argv[1].encode(): h = ((h ^ b) * 0x01000193) % 2**32
+//! print(h % 1000)' src/main.rs
+//! ```
+//!
+//! A panic without a location shows only the marker flash.
+//!
+//! Timing uses the core's cycle counter, scaled by the core clock
+//! frequency read back from the CCM, so it stays correct however the
+//! clocks have been configured.
+
+use super::{ccm, debug};
+use core::{panic::PanicInfo, ptr};
+
+/// `DEMCR`: Debug Exception and Monitor Control Register
+const DEMCR: *mut u32 = 0xE000_EDFC as *mut u32;
+/// Enables the DWT in `DEMCR`
+const DEMCR_TRCENA: u32 = 1 << 24;
+/// `DWT_CTRL`: DWT Control Register
+const DWT_CTRL: *mut u32 = 0xE000_1000 as *mut u32;
+/// Enables the cycle counter in `DWT_CTRL`
+const DWT_CTRL_CYCCNTENA: u32 = 1;
+/// `DWT_CYCCNT`: DWT Cycle Count Register
+const DWT_CYCCNT: *const u32 = 0xE000_1004 as *const u32;
+/// `DWT_LAR`: DWT Lock Access Register
+const DWT_LAR: *mut u32 = 0xE000_1FB0 as *mut u32;
+/// The key which unlocks the DWT through `DWT_LAR`
+const DWT_UNLOCK: u32 = 0xC5AC_CE55;
+
+/// The length of a short flash, and of the gap after it
+const FLASH_MS: u32 = 200;
+/// The pause between digits
+const DIGIT_PAUSE_MS: u32 = 1000;
+/// The length of the marker flash, and of the pause between numbers
+const LONG_MS: u32 = 2000;
+
+/// Busy-waits using the DWT cycle counter
+struct Delay {
+    cycles_per_ms: u32,
+}
+
+impl Delay {
+    /// # Safety
+    /// This takes over the DWT, and reads the CCM without owning it.
+    unsafe fn new() -> Delay {
+        ptr::write_volatile(DEMCR, ptr::read_volatile(DEMCR) | DEMCR_TRCENA);
+        ptr::write_volatile(DWT_LAR, DWT_UNLOCK);
+        ptr::write_volatile(DWT_CTRL, ptr::read_volatile(DWT_CTRL) | DWT_CTRL_CYCCNTENA);
+        Delay {
+            cycles_per_ms: ccm::steal_core_clock_frequency() / 1000,
+        }
+    }
+
+    fn ms(&self, ms: u32) {
+        for _ in 0..ms {
+            let start = unsafe { ptr::read_volatile(DWT_CYCCNT) };
+            let elapsed = || unsafe { ptr::read_volatile(DWT_CYCCNT) }.wrapping_sub(start);
+            while elapsed() < self.cycles_per_ms {}
+        }
+    }
+}
+
+/// Collects the decimal digits of a number, most significant first
+struct Digits {
+    digits: [u8; 10],
+    len: usize,
+}
+
+impl Digits {
+    fn new(mut value: u32, min_len: usize) -> Digits {
+        let mut digits = [0; 10];
+        let mut len = 0;
+        while value > 0 || len < min_len {
+            digits[len] = (value % 10) as u8;
+            value /= 10;
+            len += 1;
+        }
+        digits[..len].reverse();
+        Digits { digits, len }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.digits[..self.len].iter().copied()
+    }
+}
+
+/// Hash a file path with FNV-1a, as shown in the module docs
+fn file_hash(file: &str) -> u32 {
+    file.bytes().fold(0x811C_9DC5, |hash: u32, b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn blink_number(led: &mut debug::DebugPin, delay: &Delay, digits: &Digits) {
+    for digit in digits.iter() {
+        let flashes = if digit == 0 { 10 } else { digit };
+        for _ in 0..flashes {
+            led.on();
+            delay.ms(FLASH_MS);
+            led.off();
+            delay.ms(FLASH_MS);
+        }
+        delay.ms(DIGIT_PAUSE_MS);
+    }
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    let (mut led, delay) = unsafe { (debug::steal_led(), Delay::new()) };
+    let codes = info.location().map(|location| {
+        (
+            Digits::new(location.line(), 1),
+            Digits::new(file_hash(location.file()) % 1000, 3),
+        )
+    });
+
+    loop {
+        led.on();
+        delay.ms(LONG_MS);
+        led.off();
+        delay.ms(LONG_MS);
+
+        if let Some((line, file)) = &codes {
+            blink_number(&mut led, &delay, line);
+            delay.ms(LONG_MS - DIGIT_PAUSE_MS);
+            blink_number(&mut led, &delay, file);
+            delay.ms(LONG_MS - DIGIT_PAUSE_MS);
+        }
+    }
+}
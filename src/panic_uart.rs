@@ -0,0 +1,138 @@
+//! A panic handler which prints the panic over a UART
+//!
+//! This is enabled by the `panic-uart` feature. Once a panic occurs,
+//! the full panic message, including its location, is handed to
+//! `PanicUart`. Unless the program defines that itself, it takes over
+//! `LPUART6` and writes the message out on Teensy pin 1 at 115200
+//! baud, 8N1. The [fault](super::fault) handler prints through it too.
+//!
+//! A program which uses that UART or pin for something else can pick
+//! its own with [`steal`]:
+//!
+//! ```ignore
+//! use teensy40::{iomuxc::pin::GpioB1_00, lpuart::LpUart4, panic_uart};
+//!
+//! #[no_mangle]
+//! pub fn PanicUart(message: core::fmt::Arguments) {
+//!     use core::fmt::Write;
+//!
+//!     let tx = unsafe { GpioB1_00::steal() }.into_lpuart_tx();
+//!     let mut uart = unsafe { panic_uart::steal::<LpUart4<(), ()>, _, 9600>(tx) };
+//!     let _ = uart.write_fmt(message);
+//! }
+//! ```
+//!
+//! As with interrupt handlers, the signature isn't checked, so take
+//! care to match it.
+//!
+//! The UART is taken by force, so this works whether the program
+//! owns it, has only half set it up, or never touched it at all. Its
+//! clock gate is cycled and the module reset, then it's brought back
+//! up on the 24MHz oscillator. Since that reroutes the shared
+//! `UART_CLK_SEL` mux, any other UARTs in use may end up at the wrong
+//! baud rate. Stealing the pad turns on the IOMUXC clock, in case the
+//! program never did, so that the pin can be muxed.
+
+use super::{
+    ccm::{Ccm, ClockGate, UartClockInput},
+    iomuxc::pin::GpioAdB0_02,
+    lpuart::{LpUart6, Transmitter},
+};
+use core::{
+    fmt::{self, Write},
+    mem,
+    panic::PanicInfo,
+};
+
+/// The frequency of the oscillator the UART is run from
+const OSCILLATOR_HZ: u32 = 24_000_000;
+/// The oversampling ratio used for every baud rate
+const BAUD_OVERSAMPLE: u32 = 16;
+
+/// The baud rate of the default `PanicUart`
+const DEFAULT_BAUD: u32 = 115_200;
+
+/// The UART divisor giving `baud` from the oscillator, if there is one
+const fn divisor(baud: u32) -> Option<u32> {
+    let rate = match BAUD_OVERSAMPLE.checked_mul(baud) {
+        Some(rate) if rate > 0 => rate,
+        _ => return None,
+    };
+    let divisor = (OSCILLATOR_HZ + rate / 2) / rate;
+    if divisor > 0 && divisor < 1 << 13 {
+        Some(divisor)
+    } else {
+        None
+    }
+}
+
+/// Take over the UART `U`, and start it transmitting from `tx` at
+/// `BAUD`
+///
+/// The baud rate must be reachable from the 24MHz oscillator, from 184
+/// up to 1.5M. Anything else fails to build, rather than panicking
+/// again from inside the panic handler.
+///
+/// # Safety
+/// This ignores any existing owner of the CCM, and of the UART. It must only be called once nothing else will run.
+pub unsafe fn steal<U, Tx, const BAUD: u32>(tx: Tx) -> U::Output
+where
+    U: Transmitter<Tx>,
+{
+    let divisor = const {
+        match divisor(BAUD) {
+            Some(divisor) => divisor,
+            None => panic!("The panic UART baud rate can't be reached"),
+        }
+    };
+
+    let mut ccm = Ccm::steal();
+    ccm.set_clock_gate(U::GATE, ClockGate::Disabled);
+    let mut selector = ccm.uart_clock_selector_unchecked();
+    selector.set_input(UartClockInput::Oscillator);
+    selector.set_divisor(1);
+    ccm.set_clock_gate(U::GATE, ClockGate::Enabled);
+
+    // Dropping the stolen CCM would release the real owner's claim
+    mem::forget(ccm);
+
+    U::enable().disable();
+    let mut uart = U::enable();
+    uart.set_clocks(divisor, BAUD_OVERSAMPLE);
+    uart.set_tx(tx)
+}
+
+extern "Rust" {
+    fn PanicUart(message: fmt::Arguments);
+}
+
+/// Print a message through `PanicUart`
+///
+/// # Safety
+/// `PanicUart` takes over its UART, so this must only be called once
+/// nothing else will run.
+pub(crate) unsafe fn print(message: fmt::Arguments) {
+    PanicUart(message);
+}
+
+/// The `PanicUart` for any program which doesn't define its own
+///
+/// The linker script points `PanicUart` here.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub fn DefaultPanicUart_(message: fmt::Arguments) {
+    let tx = unsafe { GpioAdB0_02::steal() }.into_lpuart_tx();
+    let mut uart = unsafe { steal::<LpUart6<(), ()>, _, DEFAULT_BAUD>(tx) };
+    let _ = uart.write_fmt(message);
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    unsafe {
+        print(format_args!("\r\n{}\r\n", info));
+    }
+
+    loop {
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,378 @@
+//! Typed register access
+//!
+//! Hardware register blocks are described as `#[repr(C)]` structs of
+//! [`ReadWrite`], [`ReadOnly`] and [`Segmented`] registers. Every
+//! register is tagged with a marker type, and the fields within that
+//! register are constants tagged with the same marker. A field can
+//! therefore only be used with the register it belongs to, and mixing
+//! them up is a compile error rather than a misconfigured peripheral.
+//!
+//! Register descriptions are normally written with the crate-internal
+//! `register!` macro, which creates a module holding the marker type
+//! and its fields:
+//!
+//! ```ignore
+//! register! {
+//!     /// `CBCDR`: Bus Clock Divider Register
+//!     cbcdr {
+//!         PERIPH_CLK_SEL: Field<PeriphClockInput> = 25..26,
+//!     }
+//! }
+//!
+//! let input = regs.cbcdr.get(cbcdr::PERIPH_CLK_SEL);
+//! ```
+
+use core::{
+    marker::PhantomData,
+    ops::{BitOr, Deref, DerefMut, Range},
+};
+use volatile::Volatile;
+
+/// Describe a hardware register and its fields
+///
+/// This expands to a module named after the register. The module
+/// holds an uninhabited `Register` marker type, along with a constant
+/// for each listed field. Fields are either a [`Field`] spanning a
+/// range of bits, optionally converted to and from a richer type, or
+/// a single [`Bit`].
+macro_rules! register {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident : $kind:ident $(<$ty:ty>)? = $bits:expr
+            ),* $(,)?
+        }
+    )*) => {$(
+        $(#[$meta])*
+        #[allow(dead_code, non_camel_case_types)]
+        pub(crate) mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// Marker type for this register
+            pub enum Register {}
+
+            $(
+                $(#[$field_meta])*
+                pub const $field: $crate::register::$kind<Register $(, $ty)?> =
+                    $crate::register::$kind::new($bits);
+            )*
+        }
+    )*};
+}
+
+/// A range of bits within the register marked by `R`
+///
+/// Reading the field produces a `T`, and writing it consumes one. Most
+/// fields are plain numbers, but fields which select between a fixed
+/// set of options use the matching enum.
+pub struct Field<R, T = u32> {
+    offset: u32,
+    width: u32,
+    _marker: PhantomData<(R, T)>,
+}
+
+/// A single bit within the register marked by `R`
+pub struct Bit<R> {
+    offset: u32,
+    _marker: PhantomData<R>,
+}
+
+/// A set of bits within the register marked by `R`
+///
+/// Masks are built from [`Field`]s and [`Bit`]s, and can be combined
+/// with `|`. They are used to drive the `SET`, `CLR` and `TOG` aliases
+/// of a [`Segmented`] register.
+pub struct Mask<R> {
+    bits: u32,
+    _marker: PhantomData<R>,
+}
+
+/// A snapshot of the contents of the register marked by `R`
+pub struct Value<R> {
+    bits: u32,
+    _marker: PhantomData<R>,
+}
+
+/// A register which can be both read and written
+#[repr(transparent)]
+pub struct ReadWrite<R> {
+    value: Volatile<u32>,
+    _marker: PhantomData<R>,
+}
+
+/// A register which can only be read
+#[repr(transparent)]
+pub struct ReadOnly<R> {
+    value: Volatile<u32>,
+    _marker: PhantomData<R>,
+}
+
+/// A register followed by its `SET`, `CLR` and `TOG` aliases
+///
+/// Writing a mask to one of the aliases atomically sets, clears or
+/// toggles those bits in the register, with no read-modify-write
+/// cycle. The register itself is available through `Deref`.
+#[repr(C)]
+pub struct Segmented<R> {
+    value: ReadWrite<R>,
+    set: Volatile<u32>,
+    clear: Volatile<u32>,
+    toggle: Volatile<u32>,
+}
+
+impl<R, T> Clone for Field<R, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R, T> Copy for Field<R, T> {}
+
+impl<R> Clone for Bit<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> Copy for Bit<R> {}
+
+impl<R> Clone for Mask<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> Copy for Mask<R> {}
+
+impl<R> Clone for Value<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> Copy for Value<R> {}
+
+impl<R, T> Field<R, T> {
+    /// Describe the field occupying `bits`
+    ///
+    /// The range must be non-empty and lie within a 32-bit register.
+    pub const fn new(bits: Range<u32>) -> Self {
+        Field {
+            offset: bits.start,
+            width: bits.end - bits.start,
+            _marker: PhantomData,
+        }
+    }
+
+    fn max(self) -> u32 {
+        ((1u64 << self.width) - 1) as u32
+    }
+}
+
+impl<R> Bit<R> {
+    /// Describe the bit at `offset`
+    pub const fn new(offset: u32) -> Self {
+        Bit {
+            offset,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> Mask<R> {
+    /// The raw bits covered by this mask
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+}
+
+impl<R, T> From<Field<R, T>> for Mask<R> {
+    fn from(field: Field<R, T>) -> Mask<R> {
+        Mask {
+            bits: field.max() << field.offset,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> From<Bit<R>> for Mask<R> {
+    fn from(bit: Bit<R>) -> Mask<R> {
+        Mask {
+            bits: 1 << bit.offset,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R, M: Into<Mask<R>>> BitOr<M> for Mask<R> {
+    type Output = Mask<R>;
+
+    fn bitor(self, rhs: M) -> Mask<R> {
+        Mask {
+            bits: self.bits | rhs.into().bits,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R, M: Into<Mask<R>>> BitOr<M> for Bit<R> {
+    type Output = Mask<R>;
+
+    fn bitor(self, rhs: M) -> Mask<R> {
+        Mask::from(self) | rhs
+    }
+}
+
+impl<R> Value<R> {
+    /// Wrap a raw register value
+    pub fn from_bits(bits: u32) -> Self {
+        Value {
+            bits,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The raw register value
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    /// Read a field from this value
+    pub fn get<T: From<u32>>(self, field: Field<R, T>) -> T {
+        T::from((self.bits >> field.offset) & field.max())
+    }
+
+    /// Check whether a bit is set in this value
+    pub fn is_set(self, bit: Bit<R>) -> bool {
+        self.bits & (1 << bit.offset) != 0
+    }
+
+    /// Update a field within this value
+    ///
+    /// # Panics
+    /// The value must fit within the field.
+    pub fn set<T>(&mut self, field: Field<R, T>, value: T) -> &mut Self
+    where
+        u32: From<T>,
+    {
+        let value = u32::from(value);
+        assert!(value <= field.max());
+        self.bits &= !(field.max() << field.offset);
+        self.bits |= value << field.offset;
+        self
+    }
+
+    /// Set or clear a bit within this value
+    pub fn set_bit(&mut self, bit: Bit<R>, value: bool) -> &mut Self {
+        if value {
+            self.bits |= 1 << bit.offset;
+        } else {
+            self.bits &= !(1 << bit.offset);
+        }
+        self
+    }
+}
+
+impl<R> ReadWrite<R> {
+    /// Read the whole register
+    pub fn read(&self) -> Value<R> {
+        Value::from_bits(self.value.read())
+    }
+
+    /// Overwrite the whole register
+    pub fn write(&mut self, value: Value<R>) {
+        self.value.write(value.bits);
+    }
+
+    /// Overwrite the whole register, starting from all zeros
+    pub fn write_with<F: FnOnce(&mut Value<R>)>(&mut self, f: F) {
+        let mut value = Value::from_bits(0);
+        f(&mut value);
+        self.write(value);
+    }
+
+    /// Read-modify-write the register
+    pub fn modify<F: FnOnce(&mut Value<R>)>(&mut self, f: F) {
+        let mut value = self.read();
+        f(&mut value);
+        self.write(value);
+    }
+
+    /// Read a single field
+    pub fn get<T: From<u32>>(&self, field: Field<R, T>) -> T {
+        self.read().get(field)
+    }
+
+    /// Check whether a single bit is set
+    pub fn is_set(&self, bit: Bit<R>) -> bool {
+        self.read().is_set(bit)
+    }
+
+    /// Read-modify-write a single field
+    pub fn set<T>(&mut self, field: Field<R, T>, value: T)
+    where
+        u32: From<T>,
+    {
+        self.modify(|r| {
+            r.set(field, value);
+        });
+    }
+
+    /// Read-modify-write a single bit
+    pub fn set_bit(&mut self, bit: Bit<R>, value: bool) {
+        self.modify(|r| {
+            r.set_bit(bit, value);
+        });
+    }
+}
+
+impl<R> ReadOnly<R> {
+    /// Read the whole register
+    pub fn read(&self) -> Value<R> {
+        Value::from_bits(self.value.read())
+    }
+
+    /// Read a single field
+    pub fn get<T: From<u32>>(&self, field: Field<R, T>) -> T {
+        self.read().get(field)
+    }
+
+    /// Check whether a single bit is set
+    pub fn is_set(&self, bit: Bit<R>) -> bool {
+        self.read().is_set(bit)
+    }
+}
+
+impl<R> Segmented<R> {
+    /// Set every bit in `mask` through the `SET` alias
+    pub fn write_set<M: Into<Mask<R>>>(&mut self, mask: M) {
+        self.set.write(mask.into().bits);
+    }
+
+    /// Clear every bit in `mask` through the `CLR` alias
+    pub fn write_clear<M: Into<Mask<R>>>(&mut self, mask: M) {
+        self.clear.write(mask.into().bits);
+    }
+
+    /// Toggle every bit in `mask` through the `TOG` alias
+    pub fn write_toggle<M: Into<Mask<R>>>(&mut self, mask: M) {
+        self.toggle.write(mask.into().bits);
+    }
+}
+
+impl<R> Deref for Segmented<R> {
+    type Target = ReadWrite<R>;
+
+    fn deref(&self) -> &ReadWrite<R> {
+        &self.value
+    }
+}
+
+impl<R> DerefMut for Segmented<R> {
+    fn deref_mut(&mut self) -> &mut ReadWrite<R> {
+        &mut self.value
+    }
+}
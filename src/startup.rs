@@ -1,30 +1,186 @@
+use core::arch::asm;
+
 extern "C" {
     fn main();
     static _bss_start: u8;
     static _bss_end: u8;
+    static _sfastrun: u32;
+    static _efastrun: u32;
+    static _sifastrun: u32;
+    static _sdata: u32;
+    static _edata: u32;
+    static _sidata: u32;
+    static _stack_top: u32;
+    #[cfg(feature = "stack-guard")]
+    static _stack_guard: u32;
 }
 
+include!(concat!(env!("OUT_DIR"), "/flexram.rs"));
+
+/// The base of the IOMUXC GPR block, which holds the FlexRAM split
+const GPR: u32 = 0x400A_C000;
+/// `GPR16` bits enabling the TCMs, and taking the split from `GPR17`
+const GPR16_FLEXRAM: u32 = 0b110 | if ITCM_SIZE == 0 { 0 } else { 1 };
+
+/// `CPACR`: Coprocessor Access Control Register, where full access to
+/// CP10 and CP11 enables the FPU
+const CPACR: u32 = 0xE000_ED88;
+
+/// The entry point, running on the boot stack
+///
+/// Changing the FlexRAM split can move which physical banks back the
+/// DTCM, and the boot stack with them. It also makes the top of a
+/// larger DTCM, where the real stack lives, reachable at all. So the
+/// split is programmed without touching the stack, and then we move
+/// onto the real stack and never come back.
+///
+/// The FPU is also turned on here, as the compiler is free to use its
+/// registers in any Rust code.
 #[link_section = ".startup"]
 #[no_mangle]
 pub unsafe extern "C" fn startup() {
+    // The scratch register is an input, as outputs can't be used with
+    // noreturn. We never come back to see it clobbered.
+    asm!(
+        "str {bank_cfg}, [{gpr}, #0x44]",
+        "ldr {scratch}, [{gpr}, #0x40]",
+        "bic {scratch}, {scratch}, #7",
+        "orr {scratch}, {scratch}, {gpr16}",
+        "str {scratch}, [{gpr}, #0x40]",
+        "ldr {scratch}, [{gpr}, #0x38]",
+        "bic {scratch}, {scratch}, #0x00FF0000",
+        "orr {scratch}, {scratch}, {tcm_sizes}",
+        "str {scratch}, [{gpr}, #0x38]",
+        "ldr {scratch}, [{cpacr}]",
+        "orr {scratch}, {scratch}, #0x00F00000",
+        "str {scratch}, [{cpacr}]",
+        "dsb",
+        "isb",
+        "msr msp, {stack_top}",
+        "b {init}",
+        gpr = in(reg) GPR,
+        bank_cfg = in(reg) FLEXRAM_BANK_CFG,
+        gpr16 = in(reg) GPR16_FLEXRAM,
+        tcm_sizes = in(reg) ITCM_SIZE << 16 | DTCM_SIZE << 20,
+        cpacr = in(reg) CPACR,
+        stack_top = in(reg) &_stack_top,
+        scratch = in(reg) 0,
+        init = sym init,
+        options(noreturn),
+    );
+}
+
+/// `VTOR`: Vector Table Offset Register
+const VTOR: u32 = 0xE000_ED08;
+
+// With the itcm-text feature, everything outside of .startup only
+// works once .fastrun has been copied. Until then, even a debug build
+// mustn't call into core, so everything up to that point is written
+// in assembly.
+#[link_section = ".startup"]
+unsafe extern "C" fn init() {
+    asm!(
+        "str {vectors}, [{vtor}]",
+        vectors = in(reg) &super::bootdata::_VECTOR_TABLE as *const _ as u32,
+        vtor = in(reg) VTOR,
+        options(nostack, preserves_flags),
+    );
+    init_fastrun();
+    init_data();
     init_bss();
+    super::mpu::enable();
+    super::fault::enable();
+    #[cfg(feature = "stack-guard")]
+    init_stack_guard();
+    super::cache::enable();
     super::ccm::Ccm::new().sanitize();
     main();
 }
 
 #[link_section = ".startup"]
 unsafe fn init_bss() {
-    // This is probably fragile.
-    // As far as I can tell, the optimizer is assuming that
-    // _bss_start and _bss_end cannot be aliases for the same
-    // memory location. This causes a zero-length BSS section to
-    // blow up here. re-creating our end pointer with a bit of
-    // math seems to trick the optimizer, for now.
-    let length = (&_bss_end as *const u8 as usize) - (&_bss_start as *const u8 as usize);
-    let mut ptr = &_bss_start as *const u8 as *mut u8;
-    let end = (ptr as usize + length) as *const u8;
-    while ptr as *const u8 != end {
-        core::ptr::write_volatile(ptr, 0);
-        ptr = (ptr as usize + 1) as *mut u8;
-    }
+    asm!(
+        "2:",
+        "cmp {ptr}, {end}",
+        "bhs 3f",
+        "strb {zero}, [{ptr}], #1",
+        "b 2b",
+        "3:",
+        ptr = inout(reg) &_bss_start as *const u8 => _,
+        end = in(reg) &_bss_end as *const u8,
+        zero = in(reg) 0,
+        options(nostack),
+    );
+}
+
+#[link_section = ".startup"]
+unsafe fn init_fastrun() {
+    copy_words(&_sfastrun, &_efastrun, &_sifastrun);
+}
+
+#[link_section = ".startup"]
+unsafe fn init_data() {
+    copy_words(&_sdata, &_edata, &_sidata);
+}
+
+/// Copy a section from its load address in flash to where it runs
+///
+/// The linker script aligns both ends of each such section to a word,
+/// so it can be copied a word at a time.
+#[link_section = ".startup"]
+unsafe fn copy_words(start: &u32, end: &u32, load: &u32) {
+    asm!(
+        "2:",
+        "cmp {dst}, {end}",
+        "bhs 3f",
+        "ldr {word}, [{src}], #4",
+        "str {word}, [{dst}], #4",
+        "b 2b",
+        "3:",
+        dst = inout(reg) start as *const u32 => _,
+        end = in(reg) end as *const u32,
+        src = inout(reg) load as *const u32 => _,
+        word = out(reg) _,
+        options(nostack),
+    );
+}
+
+// The guard takes the highest numbered MPU region, so it wins over the
+// default map's DTCM region. It is 32 bytes, the smallest region the
+// MPU supports, which the linker script leaves free below the stack.
+#[cfg(feature = "stack-guard")]
+#[link_section = ".startup"]
+unsafe fn init_stack_guard() {
+    use super::mpu::{self, Access, Region};
+
+    let guard = Region::new(&_stack_guard as *const u32 as u32, 32).access(Access::NoAccess);
+    mpu::set_region(mpu::REGIONS - 1, guard);
+}
+
+/// Place functions in ITCM
+///
+/// Functions wrapped in this macro are linked into the `.fastrun`
+/// section, which is copied from flash into ITCM at startup. Code
+/// there runs at full speed without depending on the cache, which
+/// suits interrupt handlers and tight loops:
+///
+/// ```ignore
+/// teensy40::fastrun! {
+///     fn mix(samples: &mut [i16]) {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// The functions are also kept from being inlined into callers in
+/// flash. Linking fails if they don't fit in the configured ITCM.
+#[macro_export]
+macro_rules! fastrun {
+    ($($item:item)*) => {
+        $(
+            #[link_section = ".fastrun"]
+            #[inline(never)]
+            $item
+        )*
+    };
 }